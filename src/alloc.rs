@@ -0,0 +1,205 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    fmt::Display,
+    sync::{
+        Mutex, PoisonError,
+        atomic::{AtomicIsize, AtomicUsize, Ordering},
+    },
+};
+
+thread_local! {
+    /// Whether allocations made by this thread are currently being counted
+    static MEASURING: Cell<bool> = const { Cell::new(false) };
+}
+/// Held during a measurement, as the counters below are shared by all threads
+static MEASURE_LOCK: Mutex<()> = Mutex::new(());
+/// Number of allocations since the start of the measurement
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+/// Number of bytes allocated since the start of the measurement
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+/// Bytes currently live, relative to the start of the measurement (can be negative if memory allocated before the
+/// start is freed)
+static LIVE: AtomicIsize = AtomicIsize::new(0);
+/// Highest value reached by [`LIVE`] since the start of the measurement
+static PEAK: AtomicIsize = AtomicIsize::new(0);
+
+/// A global allocator which forwards to the system allocator and counts allocations when enabled
+pub struct CountingAllocator;
+
+/// Whether the current thread is being measured (never true while the thread-local is being destroyed)
+fn measuring() -> bool {
+    MEASURING.try_with(Cell::get).unwrap_or(false)
+}
+
+impl CountingAllocator {
+    /// Record an allocation of `size` bytes
+    fn on_alloc(size: usize) {
+        if !measuring() {
+            return;
+        }
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED.fetch_add(size, Ordering::Relaxed);
+        let live = LIVE.fetch_add(size as isize, Ordering::Relaxed) + size as isize;
+        PEAK.fetch_max(live, Ordering::Relaxed);
+    }
+
+    /// Record the reallocation of `old_size` bytes to `new_size` bytes, where only the growth counts as allocated
+    fn on_realloc(old_size: usize, new_size: usize) {
+        if !measuring() {
+            return;
+        }
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED.fetch_add(new_size.saturating_sub(old_size), Ordering::Relaxed);
+        let delta = new_size as isize - old_size as isize;
+        let live = LIVE.fetch_add(delta, Ordering::Relaxed) + delta;
+        PEAK.fetch_max(live, Ordering::Relaxed);
+    }
+
+    /// Record the deallocation of `size` bytes
+    fn on_dealloc(size: usize) {
+        if !measuring() {
+            return;
+        }
+        LIVE.fetch_sub(size as isize, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        Self::on_alloc(layout.size());
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        Self::on_alloc(layout.size());
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        Self::on_dealloc(layout.size());
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        Self::on_realloc(layout.size(), new_size);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+/// Allocation statistics for a measured section of code
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AllocStats {
+    /// Number of allocations (including reallocations)
+    pub allocations: usize,
+    /// Total number of bytes allocated (for reallocations, only the growth is counted)
+    pub allocated: usize,
+    /// Maximum number of live bytes at any point, in excess of what was live at the start
+    pub peak: usize,
+}
+
+impl Display for AllocStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} allocations, {} allocated, {} peak",
+            self.allocations,
+            format_bytes(self.allocated),
+            format_bytes(self.peak)
+        )
+    }
+}
+
+/// Format a number of bytes with a binary unit
+#[expect(clippy::cast_precision_loss)]
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.;
+    let mut unit = 0;
+    while value >= 1024. && unit < UNITS.len() - 1 {
+        value /= 1024.;
+        unit += 1;
+    }
+    format!("{value:.2} {}", UNITS[unit])
+}
+
+/// Run a function while counting its allocations
+///
+/// Only the allocations made by the calling thread are counted. Measurements on different threads (e.g. parallel tests)
+/// wait for each other, as they share the counters, so `f` must not call `measure` itself.
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, AllocStats) {
+    let _guard = MEASURE_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    ALLOCATIONS.store(0, Ordering::Relaxed);
+    ALLOCATED.store(0, Ordering::Relaxed);
+    LIVE.store(0, Ordering::Relaxed);
+    PEAK.store(0, Ordering::Relaxed);
+    MEASURING.set(true);
+    let res = f();
+    MEASURING.set(false);
+    let stats = AllocStats {
+        allocations: ALLOCATIONS.load(Ordering::Relaxed),
+        allocated: ALLOCATED.load(Ordering::Relaxed),
+        peak: PEAK.load(Ordering::Relaxed).max(0) as usize,
+    };
+    (res, stats)
+}
+
+/// Run a function, counting its allocations only if `enabled` is true
+pub fn measure_if<T>(enabled: bool, f: impl FnOnce() -> T) -> (T, Option<AllocStats>) {
+    if enabled {
+        let (res, stats) = measure(f);
+        (res, Some(stats))
+    } else {
+        (f(), None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure() {
+        let (v, stats) = measure(|| vec![0u8; 4096]);
+        assert_eq!(v.len(), 4096);
+        assert!(stats.allocations >= 1);
+        assert!(stats.allocated >= 4096);
+        assert!(stats.peak >= 4096);
+    }
+
+    #[test]
+    fn test_measure_realloc() {
+        const MIB: usize = 1024 * 1024;
+        let (v, stats) = measure(|| {
+            let mut v: Vec<u8> = Vec::with_capacity(MIB);
+            v.reserve_exact(2 * MIB);
+            v
+        });
+        assert!(v.capacity() >= 2 * MIB);
+        // growing from 1 MiB to 2 MiB only allocates 1 MiB more
+        assert!(stats.allocated >= 2 * MIB);
+        assert!(stats.allocated < 5 * MIB / 2);
+        assert!(stats.peak >= 2 * MIB);
+    }
+
+    #[test]
+    fn test_measure_other_threads() {
+        // allocations made by other threads (e.g. tests running in parallel) aren't counted
+        let (_, stats) = measure(|| {
+            std::thread::spawn(|| vec![0u8; 1024 * 1024])
+                .join()
+                .unwrap()
+        });
+        assert!(stats.allocated < 1024 * 1024);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.50 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.00 MiB");
+    }
+}
@@ -0,0 +1,146 @@
+use std::{collections::BTreeMap, fs, io::ErrorKind, time::Duration};
+
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::days::RunReport;
+
+/// Location of the local answers store
+const ANSWERS_FILE: &str = "inputs/answers.json";
+
+/// The last recorded answer for a part, and how long it took to compute on its own (if it was timed)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartAnswer {
+    pub answer: String,
+    #[serde(default)]
+    pub duration: Option<Duration>,
+}
+
+/// What we know about a day
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DayAnswers {
+    pub part_1: Option<PartAnswer>,
+    pub part_2: Option<PartAnswer>,
+    /// Time taken to solve both parts together
+    #[serde(default)]
+    pub duration_both: Option<Duration>,
+    /// Stars earned on the website, as last seen on the event page
+    #[serde(default)]
+    pub stars: u8,
+    /// Answers accepted on the website for each part, as last seen on the puzzle page
+    #[serde(default)]
+    pub submitted: Vec<String>,
+}
+
+impl DayAnswers {
+    /// The accepted answer for a part (1 or 2), if known
+    pub fn submitted(&self, part: usize) -> Option<&str> {
+        self.submitted.get(part - 1).map(String::as_str)
+    }
+}
+
+/// The local answers store, which records the result of each run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Answers(BTreeMap<u32, DayAnswers>);
+
+impl Answers {
+    /// Load the answers store, or create an empty one if it doesn't exist yet
+    pub fn load() -> Result<Self> {
+        match fs::read_to_string(ANSWERS_FILE) {
+            Ok(json) => serde_json::from_str(&json).context("parsing answers file"),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).context("reading answers file"),
+        }
+    }
+
+    /// Write the answers store to disk
+    pub fn save(&self) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("serializing answers")?;
+        fs::write(ANSWERS_FILE, json).context("writing answers file")
+    }
+
+    /// Get what we know about a day
+    pub fn get(&self, day: u32) -> Option<&DayAnswers> {
+        self.0.get(&day)
+    }
+
+    /// Record the answers and timings of a run
+    ///
+    /// Returns a warning for each answer which differs from the previously recorded one or from the accepted one.
+    pub fn record(&mut self, day: u32, report: &RunReport) -> Vec<String> {
+        let entry = self.0.entry(day).or_default();
+        let mut warnings = Vec::new();
+        for (part, answer) in [(1, &report.answer_1), (2, &report.answer_2)] {
+            if let Some(submitted) = entry.submitted(part)
+                && submitted != answer
+            {
+                warnings.push(format!(
+                    "part {part} answer {answer} differs from the accepted answer {submitted}"
+                ));
+            }
+            let previous = if part == 1 {
+                &entry.part_1
+            } else {
+                &entry.part_2
+            };
+            if let Some(previous) = previous
+                && previous.answer != *answer
+            {
+                warnings.push(format!(
+                    "part {part} answer changed from {} to {answer}",
+                    previous.answer
+                ));
+            }
+        }
+        entry.part_1 = Some(PartAnswer {
+            answer: report.answer_1.clone(),
+            duration: Some(report.time_1),
+        });
+        entry.part_2 = Some(PartAnswer {
+            answer: report.answer_2.clone(),
+            duration: Some(report.time_2),
+        });
+        entry.duration_both = Some(report.time_both);
+        warnings
+    }
+
+    /// Record the stars earned for a day
+    pub fn set_stars(&mut self, day: u32, stars: u8) {
+        self.0.entry(day).or_default().stars = stars;
+    }
+
+    /// Record the answers accepted on the website for a day
+    pub fn set_submitted(&mut self, day: u32, submitted: Vec<String>) {
+        self.0.entry(day).or_default().submitted = submitted;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(answer_1: &str, answer_2: &str) -> RunReport {
+        RunReport {
+            answer_1: answer_1.to_string(),
+            time_1: Duration::ZERO,
+            answer_2: answer_2.to_string(),
+            time_2: Duration::ZERO,
+            time_both: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_record_mismatch() {
+        let mut answers = Answers::default();
+        assert_eq!(answers.record(1, &report("1", "2")), Vec::<String>::new());
+        answers.set_submitted(1, vec!["1".to_string()]);
+        assert_eq!(
+            answers.record(1, &report("3", "2")),
+            [
+                "part 1 answer 3 differs from the accepted answer 1",
+                "part 1 answer changed from 1 to 3"
+            ]
+        );
+        assert_eq!(answers.get(1).unwrap().part_1.as_ref().unwrap().answer, "3");
+    }
+}
@@ -1,8 +1,15 @@
-use std::{fmt::Display, fs, path::Path, time::Instant};
+use std::{
+    fmt::Display,
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
 
-use anyhow::{Context, anyhow};
+use anyhow::{Context, anyhow, bail};
 use winnow::Parser as _;
 
+use crate::alloc::measure_if;
+
 pub mod day01;
 pub mod day02;
 pub mod day03;
@@ -16,37 +23,228 @@ pub mod day10;
 pub mod day11;
 pub mod day12;
 
+/// Call a generic function with the [`Day`] implementation for the given day number
+///
+/// Returns `None` if the day is not implemented. Add the missing days here if using this template for an older
+/// challenge.
+macro_rules! with_day {
+    ($day:expr, $f:ident($($arg:expr),*)) => {
+        match $day {
+            1 => Some($f::<$crate::days::day01::Day01>($($arg),*)),
+            2 => Some($f::<$crate::days::day02::Day02>($($arg),*)),
+            3 => Some($f::<$crate::days::day03::Day03>($($arg),*)),
+            4 => Some($f::<$crate::days::day04::Day04>($($arg),*)),
+            5 => Some($f::<$crate::days::day05::Day05>($($arg),*)),
+            6 => Some($f::<$crate::days::day06::Day06>($($arg),*)),
+            7 => Some($f::<$crate::days::day07::Day07>($($arg),*)),
+            8 => Some($f::<$crate::days::day08::Day08>($($arg),*)),
+            9 => Some($f::<$crate::days::day09::Day09>($($arg),*)),
+            10 => Some($f::<$crate::days::day10::Day10>($($arg),*)),
+            11 => Some($f::<$crate::days::day11::Day11>($($arg),*)),
+            12 => Some($f::<$crate::days::day12::Day12>($($arg),*)),
+            _ => None,
+        }
+    };
+}
+pub(crate) use with_day;
+
+/// Options for running a day
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RunOptions {
+    /// Report the allocations made while parsing and running each part
+    pub mem: bool,
+    /// Print the day's explanation of the answers, if it has one
+    pub explain: bool,
+}
+
+/// The answers of both parts for a day, along with the time it took to compute them
+#[derive(Debug, Clone)]
+pub struct RunReport {
+    pub answer_1: String,
+    /// Time taken to solve part 1 on its own
+    pub time_1: Duration,
+    pub answer_2: String,
+    /// Time taken to solve part 2 on its own
+    pub time_2: Duration,
+    /// Time taken to solve both parts at once with [`Day::solve_both`]
+    pub time_both: Duration,
+}
+
+/// Timings of repeated runs of the same code
+#[derive(Debug, Clone, Copy)]
+pub struct BenchStats {
+    pub min: Duration,
+    pub mean: Duration,
+    pub max: Duration,
+}
+
+impl BenchStats {
+    /// Run a function `iterations` times (at least once) and measure how long it takes
+    fn measure<T>(iterations: u32, mut f: impl FnMut() -> T) -> Self {
+        let iterations = iterations.max(1);
+        let mut stats = Self {
+            min: Duration::MAX,
+            mean: Duration::ZERO,
+            max: Duration::ZERO,
+        };
+        for _ in 0..iterations {
+            let before = Instant::now();
+            std::hint::black_box(f());
+            let elapsed = before.elapsed();
+            stats.min = stats.min.min(elapsed);
+            stats.max = stats.max.max(elapsed);
+            stats.mean += elapsed;
+        }
+        stats.mean /= iterations;
+        stats
+    }
+}
+
+impl Display for BenchStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "min {:.2?}, mean {:.2?}, max {:.2?}",
+            self.min, self.mean, self.max
+        )
+    }
+}
+
 pub trait Day {
-    type Input;
+    /// The parsed input, which can borrow from the input string
+    type Input<'a>;
 
-    fn parser(input_string: &mut &str) -> winnow::Result<Self::Input>;
+    fn parser<'a>(input_string: &mut &'a str) -> winnow::Result<Self::Input<'a>>;
 
     type Output1: Display;
 
-    fn part_1(input: &Self::Input) -> Self::Output1;
+    fn part_1(input: &Self::Input<'_>) -> Self::Output1;
 
     type Output2: Display;
 
-    fn part_2(input: &Self::Input) -> Self::Output2;
+    fn part_2(input: &Self::Input<'_>) -> Self::Output2;
 
-    fn parse_file(path: impl AsRef<Path>) -> anyhow::Result<Self::Input> {
-        let input_string = fs::read_to_string(path).context("reading the input file")?;
-        let input = Self::parser
-            .parse(&input_string)
+    /// Solve both parts at once
+    ///
+    /// Days where both parts share some expensive computation can override this to only perform it once.
+    fn solve_both(input: &Self::Input<'_>) -> (Self::Output1, Self::Output2) {
+        (Self::part_1(input), Self::part_2(input))
+    }
+
+    /// Explain how the answers were obtained, in a human-readable way
+    ///
+    /// Days can override this to show intermediate results which help checking the answers by eye.
+    fn explain(_input: &Self::Input<'_>) -> Option<String> {
+        None
+    }
+
+    /// Whether part 2 is a placeholder (the last day of the event has no second puzzle)
+    const PART_2_PLACEHOLDER: bool = false;
+
+    /// Parse the whole input string
+    fn parse(input_string: &str) -> anyhow::Result<Self::Input<'_>> {
+        Self::parser
+            .parse(input_string)
             .map_err(|e| anyhow!(e.to_string()))
-            .context("running the parser")?;
-        Ok(input)
+            .context("running the parser")
     }
 
-    fn run_day(path: impl AsRef<Path>) -> anyhow::Result<()> {
-        let input = Self::parse_file(path)?;
+    /// Run both parts, printing the answers and timings
+    ///
+    /// The answers come from [`Day::solve_both`], so days sharing work between the parts only do it once. Each part is
+    /// then also run on its own to get per-part timings, and checked to give the same answer. The options allow
+    /// reporting the allocations made while parsing and solving, and printing the explanation of the answers.
+    fn run_day(path: impl AsRef<Path>, options: RunOptions) -> anyhow::Result<RunReport> {
+        let RunOptions { mem, explain } = options;
+        let input_string = fs::read_to_string(path).context("reading the input file")?;
+        let (input, parse_mem) = measure_if(mem, || Self::parse(&input_string));
+        let input = input?;
+        if let Some(stats) = parse_mem {
+            println!("Parsing memory: {stats}");
+        }
+
+        let before_both = Instant::now();
+        let ((answer_1, answer_2), mem_both) = measure_if(mem, || {
+            let (answer_1, answer_2) = Self::solve_both(&input);
+            (answer_1.to_string(), answer_2.to_string())
+        });
+        let time_both = before_both.elapsed();
+        println!("Part 1: {answer_1}");
+        println!("Part 2: {answer_2}");
+        println!("Both parts took {time_both:?}");
+        if let Some(stats) = mem_both {
+            println!("Both parts memory: {stats}");
+        }
 
         let before1 = Instant::now();
-        println!("Part 1: {}", Self::part_1(&input));
-        println!("Part 1 took {:?}", before1.elapsed());
+        let (part_1, mem_1) = measure_if(mem, || Self::part_1(&input).to_string());
+        let time_1 = before1.elapsed();
+        println!("Part 1 took {time_1:?}");
+        if let Some(stats) = mem_1 {
+            println!("Part 1 memory: {stats}");
+        }
         let before2 = Instant::now();
-        println!("Part 2: {}", Self::part_2(&input));
-        println!("Part 2 took {:?}", before2.elapsed());
+        let (part_2, mem_2) = measure_if(mem, || Self::part_2(&input).to_string());
+        let time_2 = before2.elapsed();
+        println!("Part 2 took {time_2:?}");
+        if let Some(stats) = mem_2 {
+            println!("Part 2 memory: {stats}");
+        }
+        if part_1 != answer_1 || part_2 != answer_2 {
+            bail!(
+                "solving the parts separately gave different answers: part 1 expected {answer_1}, got {part_1}; \
+                part 2 expected {answer_2}, got {part_2}"
+            );
+        }
+        if explain {
+            match Self::explain(&input) {
+                Some(explanation) => println!("{explanation}"),
+                None => println!("No explanation available for this day"),
+            }
+        }
+        Ok(RunReport {
+            answer_1,
+            time_1,
+            answer_2,
+            time_2,
+            time_both,
+        })
+    }
+
+    /// Benchmark solving both parts together with [`Day::solve_both`], and each part on its own
+    ///
+    /// The input is only parsed once, each solver then runs `iterations` times.
+    fn bench(path: impl AsRef<Path>, iterations: u32) -> anyhow::Result<()> {
+        let input_string = fs::read_to_string(path).context("reading the input file")?;
+        let parsing = BenchStats::measure(iterations, || Self::parse(&input_string).is_ok());
+        let input = Self::parse(&input_string)?;
+        println!("Parsing:    {parsing}");
+        let both = BenchStats::measure(iterations, || Self::solve_both(&input));
+        println!("Both parts: {both}");
+        let part_1 = BenchStats::measure(iterations, || Self::part_1(&input));
+        println!("Part 1:     {part_1}");
+        let part_2 = BenchStats::measure(iterations, || Self::part_2(&input));
+        println!("Part 2:     {part_2}");
         Ok(())
     }
 }
+
+/// A deterministic pseudo-random number generator for tests (a 64-bit linear congruential generator)
+#[cfg(test)]
+pub struct TestRng(u64);
+
+#[cfg(test)]
+impl TestRng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// A pseudo-random number in `0..n`
+    pub fn below(&mut self, n: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        usize::try_from(self.0 >> 33).unwrap() % n
+    }
+}
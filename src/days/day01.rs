@@ -84,15 +84,15 @@ fn parse_move(input: &mut &str) -> Result<Move> {
 }
 
 impl Day for Day01 {
-    type Input = Vec<Move>;
+    type Input<'a> = Vec<Move>;
 
-    fn parser(input: &mut &str) -> Result<Self::Input> {
+    fn parser<'a>(input: &mut &'a str) -> Result<Self::Input<'a>> {
         separated(1.., parse_move, line_ending).parse_next(input)
     }
 
     type Output1 = usize;
 
-    fn part_1(input: &Self::Input) -> Self::Output1 {
+    fn part_1(input: &Self::Input<'_>) -> Self::Output1 {
         let mut dial = Dial::default();
         input.iter().fold(0, |acc, mov| {
             dial.turn(mov);
@@ -102,7 +102,7 @@ impl Day for Day01 {
 
     type Output2 = usize;
 
-    fn part_2(input: &Self::Input) -> Self::Output2 {
+    fn part_2(input: &Self::Input<'_>) -> Self::Output2 {
         let mut dial = Dial::default();
         input.iter().map(|m| dial.turn(m)).sum()
     }
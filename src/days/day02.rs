@@ -62,15 +62,15 @@ fn parse_range(input: &mut &str) -> Result<RangeInclusive<usize>> {
 }
 
 impl Day for Day02 {
-    type Input = Vec<RangeInclusive<usize>>;
+    type Input<'a> = Vec<RangeInclusive<usize>>;
 
-    fn parser(input: &mut &str) -> Result<Self::Input> {
+    fn parser<'a>(input: &mut &'a str) -> Result<Self::Input<'a>> {
         separated(1.., parse_range, ',').parse_next(input)
     }
 
     type Output1 = usize;
 
-    fn part_1(input: &Self::Input) -> Self::Output1 {
+    fn part_1(input: &Self::Input<'_>) -> Self::Output1 {
         input
             .iter()
             .flat_map(Clone::clone)
@@ -83,7 +83,7 @@ impl Day for Day02 {
 
     type Output2 = usize;
 
-    fn part_2(input: &Self::Input) -> Self::Output2 {
+    fn part_2(input: &Self::Input<'_>) -> Self::Output2 {
         let mut res = 0;
         let mut set = HashSet::new(); // deduplicate IDs of interest
         // generate all possible IDs up to `MAX_DIGITS` in length
@@ -66,21 +66,21 @@ fn parse_bank(input: &mut &str) -> Result<Bank> {
 }
 
 impl Day for Day03 {
-    type Input = Vec<Bank>;
+    type Input<'a> = Vec<Bank>;
 
-    fn parser(input: &mut &str) -> Result<Self::Input> {
+    fn parser<'a>(input: &mut &'a str) -> Result<Self::Input<'a>> {
         separated(1.., parse_bank, newline).parse_next(input)
     }
 
     type Output1 = usize;
 
-    fn part_1(input: &Self::Input) -> Self::Output1 {
+    fn part_1(input: &Self::Input<'_>) -> Self::Output1 {
         input.iter().map(|b| b.max_joltage(2)).sum()
     }
 
     type Output2 = usize;
 
-    fn part_2(input: &Self::Input) -> Self::Output2 {
+    fn part_2(input: &Self::Input<'_>) -> Self::Output2 {
         input.iter().map(|b| b.max_joltage(12)).sum()
     }
 }
@@ -67,9 +67,9 @@ fn parse_line(input: &mut &str) -> Result<Vec<bool>> {
 }
 
 impl Day for Day04 {
-    type Input = Grid;
+    type Input<'a> = Grid;
 
-    fn parser(input: &mut &str) -> Result<Self::Input> {
+    fn parser<'a>(input: &mut &'a str) -> Result<Self::Input<'a>> {
         let lines: Vec<_> = separated(1.., parse_line, line_ending).parse_next(input)?;
         let mut grid = HashSet::new();
         for (y, line) in lines.into_iter().enumerate() {
@@ -88,13 +88,13 @@ impl Day for Day04 {
 
     type Output1 = usize;
 
-    fn part_1(input: &Self::Input) -> Self::Output1 {
+    fn part_1(input: &Self::Input<'_>) -> Self::Output1 {
         get_accessible(input).unwrap().len()
     }
 
     type Output2 = usize;
 
-    fn part_2(input: &Self::Input) -> Self::Output2 {
+    fn part_2(input: &Self::Input<'_>) -> Self::Output2 {
         let mut grid = input.clone();
         let init_rolls = grid.0.len();
         while let Some(accessible) = get_accessible(&grid) {
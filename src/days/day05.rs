@@ -22,9 +22,9 @@ fn parse_ranges(input: &mut &str) -> Result<Vec<RangeInclusive<usize>>> {
 }
 
 impl Day for Day05 {
-    type Input = (Vec<RangeInclusive<usize>>, Vec<usize>);
+    type Input<'a> = (Vec<RangeInclusive<usize>>, Vec<usize>);
 
-    fn parser(input: &mut &str) -> Result<Self::Input> {
+    fn parser<'a>(input: &mut &'a str) -> Result<Self::Input<'a>> {
         separated_pair(
             parse_ranges,
             (newline, newline),
@@ -35,7 +35,7 @@ impl Day for Day05 {
 
     type Output1 = usize;
 
-    fn part_1(input: &Self::Input) -> Self::Output1 {
+    fn part_1(input: &Self::Input<'_>) -> Self::Output1 {
         let (ranges, ingredients) = input;
         ingredients
             .iter()
@@ -45,7 +45,7 @@ impl Day for Day05 {
 
     type Output2 = usize;
 
-    fn part_2(input: &Self::Input) -> Self::Output2 {
+    fn part_2(input: &Self::Input<'_>) -> Self::Output2 {
         let (ranges, _) = input;
         // sorting the ranges by start ID so that we can merge them easily in one pass
         let mut ranges = ranges.clone();
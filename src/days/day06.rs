@@ -53,9 +53,9 @@ fn parse_operators_line(input: &mut &str) -> Result<Vec<Operator>> {
 }
 
 impl Day for Day06 {
-    type Input = (Vec<Problem>, Vec<Problem>); // part 1, part 2
+    type Input<'a> = (Vec<Problem>, Vec<Problem>); // part 1, part 2
 
-    fn parser(input: &mut &str) -> Result<Self::Input> {
+    fn parser<'a>(input: &mut &'a str) -> Result<Self::Input<'a>> {
         // parse the characters of a digits line, including the spaces
         let digits: Vec<Vec<char>> = separated(
             1..,
@@ -142,14 +142,14 @@ impl Day for Day06 {
 
     type Output1 = usize;
 
-    fn part_1(input: &Self::Input) -> Self::Output1 {
+    fn part_1(input: &Self::Input<'_>) -> Self::Output1 {
         let (input, _) = input;
         input.iter().map(Problem::compute).sum()
     }
 
     type Output2 = usize;
 
-    fn part_2(input: &Self::Input) -> Self::Output2 {
+    fn part_2(input: &Self::Input<'_>) -> Self::Output2 {
         let (_, input) = input;
         input.iter().map(Problem::compute).sum()
     }
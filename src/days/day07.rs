@@ -1,8 +1,6 @@
-use std::{
-    collections::{BTreeMap, BTreeSet},
-    num::NonZeroUsize,
-};
+use std::fmt::Display;
 
+use num::{BigUint, CheckedAdd, Zero};
 use winnow::{
     Parser as _, Result,
     ascii::newline,
@@ -12,126 +10,164 @@ use winnow::{
 
 use crate::days::Day;
 
-const LINES: i32 = 142;
-const COLUMNS: i32 = 141;
-
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Point {
-    y: i32,
-    x: i32,
+#[derive(Debug, Clone)]
+pub struct Manifold {
+    /// Column of the start point, on the first line
+    start: usize,
+    /// Number of columns
+    width: usize,
+    /// For each line below the start, whether each column has a splitter
+    splitters: Vec<Vec<bool>>,
 }
 
-impl Point {
-    /// Gets the position directly above self
-    fn above(self) -> Point {
-        Point {
-            y: self.y - 1,
-            x: self.x,
+impl Manifold {
+    /// Build the manifold from the parsed grid, if all the lines have the same width and the first one has the start
+    fn from_grid(grid: &[Vec<char>]) -> Option<Self> {
+        let width = grid[0].len();
+        if grid.iter().any(|line| line.len() != width) {
+            return None;
         }
+        let start = grid[0].iter().position(|c| *c == 'S')?;
+        let splitters = grid[1..]
+            .iter()
+            .map(|line| line.iter().map(|c| *c == '^').collect())
+            .collect();
+        Some(Self {
+            start,
+            width,
+            splitters,
+        })
     }
 
-    /// Gets the position directly below self
-    fn below(self) -> Point {
-        Point {
-            y: self.y + 1,
-            x: self.x,
+    /// Count the splits, only following which columns the beams reach on each line
+    fn splits(&self) -> usize {
+        let mut beams = vec![false; self.width];
+        beams[self.start] = true;
+        let mut n_splits = 0;
+        for line in &self.splitters {
+            let mut below = vec![false; self.width];
+            for x in (0..self.width).filter(|x| beams[*x]) {
+                if !line[x] {
+                    below[x] = true;
+                    continue;
+                }
+                n_splits += 1;
+                if let Some(left) = x.checked_sub(1) {
+                    below[left] = true;
+                }
+                if let Some(right) = below.get_mut(x + 1) {
+                    *right = true;
+                }
+            }
+            beams = below;
         }
+        n_splits
     }
 
-    /// Gets the position directly to the left of self
-    fn left(self) -> Point {
-        Point {
-            y: self.y,
-            x: self.x - 1,
+    /// Project the beams down to the bottom of the manifold line by line, keeping track of how many possible paths go
+    /// through each column and counting the splits. If the path counts overflow `N`, stops with the beams above the
+    /// line where they did.
+    fn project_beams<N: Clone + Zero + CheckedAdd>(
+        &self,
+        mut beams: Beams<N>,
+    ) -> std::result::Result<Beams<N>, Beams<N>> {
+        while let Some(line) = self.splitters.get(beams.line) {
+            let mut below = vec![N::zero(); self.width];
+            let mut n_splits = 0;
+            for (x, n) in beams.paths.iter().enumerate().filter(|(_, n)| !n.is_zero()) {
+                if !line[x] {
+                    // propagate the beam down keeping the same count
+                    let Some(sum) = below[x].checked_add(n) else {
+                        return Err(beams);
+                    };
+                    below[x] = sum;
+                    continue;
+                }
+                // the splitter receives `n` possible beams as input
+                n_splits += 1;
+                // split the beam, indicating that `n` paths are propagated to either side (unless they leave the
+                // manifold), summing with any existing paths count at that location
+                for side in [x.checked_sub(1), Some(x + 1)].into_iter().flatten() {
+                    let Some(count) = below.get_mut(side) else {
+                        continue;
+                    };
+                    let Some(sum) = count.checked_add(n) else {
+                        return Err(beams);
+                    };
+                    *count = sum;
+                }
+            }
+            beams = Beams {
+                line: beams.line + 1,
+                splits: beams.splits + n_splits,
+                paths: below,
+            };
         }
+        Ok(beams)
     }
 
-    /// Gets the position directly to the right of self
-    fn right(self) -> Point {
-        Point {
-            y: self.y,
-            x: self.x + 1,
-        }
+    /// Count the splits and timelines, with 128-bit integers until the counts are too large for them, then with big
+    /// integers from the line where they overflowed
+    fn timelines(&self) -> (usize, Timelines) {
+        let mut paths = vec![0; self.width];
+        paths[self.start] = 1; // 1 path goes through the initial beam
+        let beams = Beams {
+            line: 0,
+            splits: 0,
+            paths,
+        };
+        let beams = match self.project_beams::<u128>(beams) {
+            Ok(beams) => {
+                let total = beams
+                    .paths
+                    .iter()
+                    .try_fold(0u128, |total, n| total.checked_add(*n));
+                if let Some(total) = total {
+                    return (beams.splits, Timelines::Count(total));
+                }
+                beams // only the total overflows
+            }
+            Err(beams) => beams,
+        };
+        let beams = Beams {
+            line: beams.line,
+            splits: beams.splits,
+            paths: beams.paths.into_iter().map(BigUint::from).collect(),
+        };
+        let beams = self
+            .project_beams(beams)
+            .unwrap_or_else(|_| unreachable!("big integers don't overflow"));
+        (beams.splits, Timelines::Big(beams.paths.into_iter().sum()))
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct Manifold {
-    /// Start point
-    start: Point,
-    /// Positions of all splitters
-    splitters: BTreeSet<Point>,
-    /// For each beam position, how many paths go through here
-    beams: BTreeMap<Point, NonZeroUsize>,
+/// The beams partway down the manifold
+struct Beams<N> {
+    /// Index of the next line of splitters they reach
+    line: usize,
+    /// Number of splits so far
+    splits: usize,
+    /// Number of possible paths through each column
+    paths: Vec<N>,
 }
 
-impl Manifold {
-    /// Return whether a beam enter the splitter, and how many paths go through that position
-    fn receives_beam(&self, splitter: Point) -> Option<NonZeroUsize> {
-        self.beams.get(&splitter.above()).copied()
-    }
+/// The number of timelines, which grows exponentially with the depth of the manifold
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Timelines {
+    Count(u128),
+    /// Too many timelines to fit in 128 bits
+    Big(BigUint),
+}
 
-    /// Project the source beam into the manifold, keeping track of how many possible paths go through each position
-    /// and recording the total number of splits (the return value).
-    fn project_beams(&mut self) -> usize {
-        self.beams.insert(
-            Point {
-                y: 1,
-                x: self.start.x,
-            },
-            NonZeroUsize::MIN, // 1 path goes through the initial beam
-        );
-        let mut n_splits = 0; // counter for total splits
-        for y in 2..LINES {
-            let range = Point { y, x: 0 }..Point { y, x: COLUMNS };
-            if y % 2 == 0 {
-                // check all splitters on this line (only even lines have splitters)
-                for p in self.splitters.range(range.clone()) {
-                    let Some(n) = self.receives_beam(*p) else {
-                        // the splitter doesn't receive any beam as input
-                        continue;
-                    };
-                    // the splitter receives `n` possible beams as input
-                    n_splits += 1; // count the splitter
-                    // split the beam, indicating that `n` paths are propagated to either side
-                    // and potentially summing with any existing paths count at that location
-                    let n_left = *self
-                        .beams
-                        .entry(p.left())
-                        .and_modify(|p| *p = p.saturating_add(n.into()))
-                        .or_insert(n);
-                    let n_right = *self
-                        .beams
-                        .entry(p.right())
-                        .and_modify(|p| *p = p.saturating_add(n.into()))
-                        .or_insert(n);
-                    // propagate the beams down to the next line which doesn't contain splitters
-                    // keeping the same number of paths
-                    self.beams.insert(p.left().below(), n_left);
-                    self.beams.insert(p.right().below(), n_right);
-                }
-            }
-            // check all beams on this line (including the ones we just split)
-            for (p, n) in self
-                .beams
-                .range(range)
-                .map(|(p, n)| (*p, *n))
-                .collect::<Vec<_>>()
-            {
-                // propagate beams down keeping the same count
-                // but only if there isn't a splitter there
-                let below = p.below();
-                if !self.splitters.contains(&below) {
-                    self.beams.insert(below, n);
-                }
-            }
+impl Display for Timelines {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Timelines::Count(n) => n.fmt(f),
+            Timelines::Big(n) => n.fmt(f),
         }
-        n_splits
     }
 }
 
-impl Manifold {}
-
 pub struct Day07;
 
 fn parse_line(input: &mut &str) -> Result<Vec<char>> {
@@ -143,65 +179,37 @@ fn parse_grid(input: &mut &str) -> Result<Vec<Vec<char>>> {
 }
 
 impl Day for Day07 {
-    type Input = Manifold;
+    type Input<'a> = Manifold;
 
-    fn parser(input: &mut &str) -> Result<Self::Input> {
-        let grid = parse_grid.parse_next(input)?;
-        let start_x = grid
-            .first()
-            .unwrap()
-            .iter()
-            .position(|c| c == &'S')
-            .unwrap();
-        let mut splitters = BTreeSet::new();
-        for (y, line) in grid.iter().enumerate().skip(1) {
-            for (x, cell) in line.iter().enumerate() {
-                if cell == &'^' {
-                    splitters.insert(Point {
-                        y: y as i32,
-                        x: x as i32,
-                    });
-                }
-            }
-        }
-        Ok(Manifold {
-            start: Point {
-                y: 0,
-                x: start_x as i32,
-            },
-            splitters,
-            beams: BTreeMap::new(),
-        })
+    fn parser<'a>(input: &mut &'a str) -> Result<Self::Input<'a>> {
+        parse_grid
+            .verify_map(|grid| Manifold::from_grid(&grid))
+            .parse_next(input)
     }
 
     type Output1 = usize;
 
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        let mut manifold = input.clone();
-        manifold.project_beams()
+    fn part_1(input: &Self::Input<'_>) -> Self::Output1 {
+        input.splits()
     }
 
-    type Output2 = usize;
+    type Output2 = Timelines;
 
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        let mut manifold = input.clone();
-        manifold.project_beams();
-        // sum up all the paths reaching the last line
-        let last_line = Point { y: LINES - 1, x: 0 }..Point {
-            y: LINES - 1,
-            x: COLUMNS,
-        };
-        manifold
-            .beams
-            .range(last_line)
-            .map(|(_, paths)| usize::from(*paths))
-            .sum()
+    fn part_2(input: &Self::Input<'_>) -> Self::Output2 {
+        input.timelines().1
+    }
+
+    fn solve_both(input: &Self::Input<'_>) -> (Self::Output1, Self::Output2) {
+        // both parts need the beams to be projected, we only do it once
+        input.timelines()
     }
 }
 
 #[cfg(test)]
 #[expect(const_item_mutation)]
 mod tests {
+    use num::One as _;
+
     use super::*;
 
     const INPUT: &str = ".......S.......
@@ -230,6 +238,63 @@ mod tests {
     #[test]
     fn test_part2() {
         let parsed = Day07::parser(&mut INPUT).unwrap();
-        assert_eq!(Day07::part_2(&parsed), 40);
+        assert_eq!(Day07::part_2(&parsed), Timelines::Count(40));
+    }
+
+    #[test]
+    fn test_solve_both() {
+        let parsed = Day07::parser(&mut INPUT).unwrap();
+        assert_eq!(Day07::solve_both(&parsed), (21, Timelines::Count(40)));
+    }
+
+    #[test]
+    fn test_any_line() {
+        // splitters on consecutive lines, in a grid of any size
+        let parsed = Day07::parse("..S..\n..^..\n.^.^.\n.....").unwrap();
+        assert_eq!(Day07::solve_both(&parsed), (3, Timelines::Count(4)));
+        assert!(Day07::parse("..S..\n...").is_err());
+        assert!(Day07::parse(".....\n.....").is_err());
+    }
+
+    /// A manifold where every beam reaches a splitter on each line, so the number of timelines doubles on every line
+    fn doubling_manifold(depth: usize) -> String {
+        let width = 2 * depth + 1;
+        let mut lines = vec![format!("{}S{}", ".".repeat(depth), ".".repeat(depth))];
+        for y in 0..depth {
+            lines.push(
+                (0..width)
+                    .map(|x| {
+                        if (x + y + depth).is_multiple_of(2) {
+                            '^'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect(),
+            );
+        }
+        lines.join("\n")
+    }
+
+    #[test]
+    fn test_deep_manifold() {
+        let parsed = Day07::parse(&doubling_manifold(100)).unwrap();
+        assert_eq!(Day07::part_2(&parsed), Timelines::Count(1 << 100));
+        // 2^130 timelines overflow 128 bits
+        let parsed = Day07::parse(&doubling_manifold(130)).unwrap();
+        let (n_splits, timelines) = Day07::solve_both(&parsed);
+        assert_eq!(n_splits, (1..=130).sum::<usize>());
+        assert_eq!(timelines, Timelines::Big(BigUint::one() << 130));
+        assert_eq!(
+            timelines.to_string(),
+            "1361129467683753853853498429727072845824"
+        );
+        assert_eq!(Day07::part_1(&parsed), n_splits);
+        // 2^128 timelines, where only the total overflows and not the count for any column
+        let parsed = Day07::parse(&doubling_manifold(128)).unwrap();
+        assert_eq!(
+            Day07::part_2(&parsed),
+            Timelines::Big(BigUint::one() << 128)
+        );
     }
 }
@@ -1,11 +1,7 @@
-use std::{cmp::Reverse, collections::HashSet};
+use std::{cmp::Reverse, collections::BinaryHeap, fmt::Write as _};
 
 use itertools::Itertools;
-use petgraph::{
-    algo::connected_components,
-    graph::{NodeIndex, UnGraph},
-    visit::DfsPostOrder,
-};
+use serde::Serialize;
 use winnow::{
     Parser as _, Result,
     ascii::{dec_uint, newline},
@@ -33,22 +29,360 @@ impl Point {
     }
 }
 
-/// Calculate the distances between all pairs of junction boxes and return them in ascending distance order
-fn get_all_dist_sorted(graph: &UnGraph<Point, ()>) -> Vec<(usize, NodeIndex, NodeIndex)> {
-    let mut dist = Vec::new();
-    for pair in graph.node_indices().combinations_with_replacement(2) {
-        let [a, b, ..] = pair.as_slice() else {
-            unreachable!();
+impl Point {
+    /// Get the coordinate along an axis (0 for x, 1 for y, 2 for z)
+    fn coord(&self, axis: usize) -> usize {
+        match axis {
+            0 => self.x,
+            1 => self.y,
+            _ => self.z,
+        }
+    }
+}
+
+/// A k-d tree over the junction boxes, stored implicitly as a permutation of their indices
+///
+/// The median of each sub-slice (along the axis corresponding to its depth) is the node splitting the slice in two.
+#[derive(Debug, Clone)]
+struct KdTree<'a> {
+    points: &'a [Point],
+    indices: Vec<usize>,
+}
+
+impl<'a> KdTree<'a> {
+    /// Build the tree by recursively partitioning around the median
+    fn new(points: &'a [Point]) -> Self {
+        fn build(points: &[Point], indices: &mut [usize], depth: usize) {
+            if indices.len() <= 1 {
+                return;
+            }
+            let axis = depth % 3;
+            let mid = indices.len() / 2;
+            indices.select_nth_unstable_by_key(mid, |i| points[*i].coord(axis));
+            let (left, right) = indices.split_at_mut(mid);
+            build(points, left, depth + 1);
+            build(points, &mut right[1..], depth + 1);
+        }
+        let mut indices: Vec<_> = (0..points.len()).collect();
+        build(points, &mut indices, 0);
+        Self { points, indices }
+    }
+
+    /// Find the `k` nearest neighbours of a junction box (excluding itself), in ascending `(distance, index)` order
+    fn nearest(&self, from: usize, k: usize) -> Vec<(usize, usize)> {
+        let mut best = BinaryHeap::with_capacity(k + 1); // max-heap of the best candidates so far
+        self.search(&self.indices, 0, from, k, &mut best);
+        best.into_sorted_vec()
+    }
+
+    /// Recursively search a sub-tree for nearest neighbours
+    fn search(
+        &self,
+        indices: &[usize],
+        depth: usize,
+        from: usize,
+        k: usize,
+        best: &mut BinaryHeap<(usize, usize)>,
+    ) {
+        if indices.is_empty() {
+            return;
+        }
+        let mid = indices.len() / 2;
+        let node = indices[mid];
+        let target = &self.points[from];
+        if node != from {
+            let candidate = (target.dist_squared(&self.points[node]), node);
+            if best.len() < k {
+                best.push(candidate);
+            } else if candidate < *best.peek().unwrap() {
+                best.pop();
+                best.push(candidate);
+            }
+        }
+        let axis = depth % 3;
+        let (left, right) = (&indices[..mid], &indices[mid + 1..]);
+        let (near, far) = if target.coord(axis) < self.points[node].coord(axis) {
+            (left, right)
+        } else {
+            (right, left)
         };
+        self.search(near, depth + 1, from, k, best);
+        // only visit the other side of the splitting plane if it could contain closer points
+        let plane_dist = target.coord(axis).abs_diff(self.points[node].coord(axis));
+        if best.len() < k || plane_dist * plane_dist <= best.peek().unwrap().0 {
+            self.search(far, depth + 1, from, k, best);
+        }
+    }
+}
+
+/// A lazy stream of all pairs of junction boxes `(distance, a, b)` in ascending distance order (then by index)
+///
+/// Each junction box `a` keeps a buffer of its nearest neighbours, and a min-heap yields the closest next pair across
+/// all junction boxes. A pair is only yielded from the junction box with the smaller index, so that it appears once.
+/// When a buffer is exhausted, it's refilled by querying twice as many neighbours from the k-d tree.
+#[derive(Debug, Clone)]
+struct NearestPairs<'a> {
+    tree: KdTree<'a>,
+    /// For each junction box, its nearest neighbours in ascending order, and how many of them were already consumed
+    neighbours: Vec<(Vec<(usize, usize)>, usize)>,
+    /// The next pair for each junction box which still has some
+    heap: BinaryHeap<Reverse<(usize, usize, usize)>>,
+}
+
+impl<'a> NearestPairs<'a> {
+    /// Number of neighbours to query initially for each junction box
+    const INITIAL_NEIGHBOURS: usize = 8;
+
+    fn new(points: &'a [Point]) -> Self {
+        let tree = KdTree::new(points);
+        let mut pairs = Self {
+            neighbours: (0..points.len())
+                .map(|a| (tree.nearest(a, Self::INITIAL_NEIGHBOURS), 0))
+                .collect(),
+            tree,
+            heap: BinaryHeap::with_capacity(points.len()),
+        };
+        for a in 0..points.len() {
+            pairs.push_next(a);
+        }
+        pairs
+    }
+
+    /// Push the next pair for junction box `a` (with a neighbour of larger index) onto the heap, if any
+    fn push_next(&mut self, a: usize) {
+        loop {
+            let (buffer, consumed) = &mut self.neighbours[a];
+            if let Some((dist, b)) = buffer.get(*consumed).copied() {
+                *consumed += 1;
+                if b > a {
+                    self.heap.push(Reverse((dist, a, b)));
+                    return;
+                }
+                continue;
+            }
+            if buffer.len() >= self.tree.points.len() - 1 {
+                return; // all neighbours were seen
+            }
+            // refill with more neighbours, the ones we already consumed come first as the order is deterministic
+            *buffer = self.tree.nearest(a, buffer.len() * 2);
+        }
+    }
+}
+
+impl Iterator for NearestPairs<'_> {
+    type Item = (usize, usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((dist, a, b)) = self.heap.pop()?;
+        self.push_next(a);
+        Some((dist, a, b))
+    }
+}
+
+/// A disjoint-set (union-find) structure keeping track of which net each junction box belongs to
+#[derive(Debug, Clone)]
+struct Nets {
+    /// Parent of each junction box in its net's tree, the root being the representative of the net
+    parent: Vec<usize>,
+    /// Number of junction boxes in each net, only meaningful for roots
+    size: Vec<usize>,
+    /// Number of distinct nets
+    count: usize,
+}
+
+impl Nets {
+    /// Create a structure where each junction box is in its own net
+    fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+            size: vec![1; len],
+            count: len,
+        }
+    }
+
+    /// Find the representative of the net containing junction box `a`
+    fn find(&mut self, mut a: usize) -> usize {
+        while self.parent[a] != a {
+            // path halving: point to the grand-parent to flatten the tree as we go
+            self.parent[a] = self.parent[self.parent[a]];
+            a = self.parent[a];
+        }
+        a
+    }
+
+    /// Connect two junction boxes, merging their nets
+    ///
+    /// Returns `false` if they were already part of the same net.
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
         if a == b {
-            continue;
+            return false;
+        }
+        // attach the smaller tree below the larger one
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
         }
-        let pa = graph.node_weight(*a).unwrap();
-        let pb = graph.node_weight(*b).unwrap();
-        dist.push((pa.dist_squared(pb), *a, *b));
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.count -= 1;
+        true
+    }
+
+    /// Get the size of each net
+    fn sizes(&self) -> impl Iterator<Item = usize> {
+        self.parent
+            .iter()
+            .enumerate()
+            .filter(|(i, p)| i == *p)
+            .map(|(i, _)| self.size[i])
+    }
+}
+
+/// Multiply the sizes of the 3 largest nets, ignoring unconnected junction boxes
+fn largest_nets_product(nets: &Nets) -> usize {
+    nets.sizes()
+        .filter(|s| *s > 1)
+        .sorted_unstable_by_key(|s| Reverse(*s))
+        .take(3)
+        .product()
+}
+
+/// The merge of two clusters in a single-linkage dendrogram
+///
+/// Leaves (junction boxes) are clusters `0..n`, and the `i`-th merge creates cluster `n + i`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Merge {
+    /// First merged cluster
+    pub left: usize,
+    /// Second merged cluster
+    pub right: usize,
+    /// Squared distance of the pair which linked the two clusters
+    pub dist: usize,
+    /// Number of junction boxes in the resulting cluster
+    pub size: usize,
+    /// Number of connections (pairs considered in ascending distance order) when the merge happened
+    pub edges: usize,
+}
+
+/// The full merge history of single-linkage clustering of the junction boxes
+#[derive(Debug, Clone, Serialize)]
+pub struct Dendrogram {
+    /// Number of junction boxes
+    pub leaves: usize,
+    pub merges: Vec<Merge>,
+}
+
+impl Dendrogram {
+    /// Connect pairs in ascending distance order, recording each merge until all junction boxes form a single cluster
+    pub fn new(points: &[Point]) -> Self {
+        let leaves = points.len();
+        let mut nets = Nets::new(leaves);
+        // the cluster ID of each net, indexed by the net's representative
+        let mut cluster: Vec<_> = (0..leaves).collect();
+        let mut merges = Vec::with_capacity(leaves.saturating_sub(1));
+        for (edges, (dist, a, b)) in NearestPairs::new(points).enumerate() {
+            if nets.count == 1 {
+                break;
+            }
+            let (left, right) = (cluster[nets.find(a)], cluster[nets.find(b)]);
+            if !nets.union(a, b) {
+                continue;
+            }
+            let root = nets.find(a);
+            cluster[root] = leaves + merges.len();
+            merges.push(Merge {
+                left,
+                right,
+                dist,
+                size: nets.size[root],
+                edges: edges + 1,
+            });
+        }
+        Self { leaves, merges }
+    }
+
+    /// Get the sizes of the clusters (in descending order) formed by the merges satisfying the predicate
+    ///
+    /// Since merges happen in ascending distance order, we stop at the first merge which doesn't satisfy it.
+    fn sizes_while(&self, pred: impl Fn(&Merge) -> bool) -> Vec<usize> {
+        let mut sizes: Vec<_> = vec![1; self.leaves];
+        let mut alive = vec![true; self.leaves];
+        for merge in self.merges.iter().take_while(|m| pred(m)) {
+            alive[merge.left] = false;
+            alive[merge.right] = false;
+            sizes.push(merge.size);
+            alive.push(true);
+        }
+        sizes
+            .into_iter()
+            .zip(alive)
+            .filter_map(|(s, a)| a.then_some(s))
+            .sorted_unstable_by_key(|s| Reverse(*s))
+            .collect()
+    }
+
+    /// Get the sizes of the clusters (in descending order) when only pairs up to a squared distance are connected
+    pub fn sizes_at_distance(&self, max_dist: usize) -> Vec<usize> {
+        self.sizes_while(|m| m.dist <= max_dist)
+    }
+
+    /// Get the sizes of the clusters (in descending order) after connecting the given number of closest pairs
+    pub fn sizes_at_edges(&self, edges: usize) -> Vec<usize> {
+        self.sizes_while(|m| m.edges <= edges)
+    }
+
+    /// Export the dendrogram in the Newick format
+    ///
+    /// Leaves are labelled with the index of the junction box in the input, and branch lengths are the differences of
+    /// euclidean distances (not squared) between a cluster and its parent.
+    #[expect(clippy::cast_precision_loss)]
+    pub fn to_newick(&self) -> String {
+        // steps of the iterative traversal, to avoid overflowing the stack on deep trees
+        enum Step {
+            Open(usize, Option<usize>),
+            Comma,
+            Close(usize, Option<usize>),
+        }
+        let height = |cluster: usize| {
+            if cluster < self.leaves {
+                0.
+            } else {
+                (self.merges[cluster - self.leaves].dist as f64).sqrt()
+            }
+        };
+        let Some(root) = (self.leaves + self.merges.len()).checked_sub(1) else {
+            return ";".to_string();
+        };
+        let mut out = String::new();
+        let mut stack = vec![Step::Open(root, None)];
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Open(cluster, parent) if cluster < self.leaves => {
+                    out.push_str(&cluster.to_string());
+                    if let Some(parent) = parent {
+                        write!(out, ":{}", height(parent)).unwrap();
+                    }
+                }
+                Step::Open(cluster, parent) => {
+                    let merge = &self.merges[cluster - self.leaves];
+                    out.push('(');
+                    stack.push(Step::Close(cluster, parent));
+                    stack.push(Step::Open(merge.right, Some(cluster)));
+                    stack.push(Step::Comma);
+                    stack.push(Step::Open(merge.left, Some(cluster)));
+                }
+                Step::Comma => out.push(','),
+                Step::Close(cluster, parent) => {
+                    out.push(')');
+                    if let Some(parent) = parent {
+                        write!(out, ":{}", height(parent) - height(cluster)).unwrap();
+                    }
+                }
+            }
+        }
+        out.push(';');
+        out
     }
-    dist.sort_unstable_by_key(|(d, _, _)| *d);
-    dist
 }
 
 pub struct Day08;
@@ -60,82 +394,66 @@ fn parse_point(input: &mut &str) -> Result<Point> {
 }
 
 impl Day for Day08 {
-    type Input = UnGraph<Point, ()>;
+    type Input<'a> = Vec<Point>;
 
-    fn parser(input: &mut &str) -> Result<Self::Input> {
-        let points: Vec<_> = separated(1.., parse_point, newline).parse_next(input)?;
-        // construct graph with all unconnected nodes
-        let mut graph = UnGraph::with_capacity(points.len(), 1000);
-        for p in points {
-            graph.add_node(p);
-        }
-        Ok(graph)
+    fn parser<'a>(input: &mut &'a str) -> Result<Self::Input<'a>> {
+        separated(1.., parse_point, newline).parse_next(input)
     }
 
     type Output1 = usize;
 
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        let mut graph = input.clone();
-        // compute all pairs' distances and iterate over them in ascending order up to the number
-        // of connections required
-        let dist = get_all_dist_sorted(&graph);
-        for (_, a, b) in dist.into_iter().take(NUM_CONNECTIONS) {
-            // link the two junctions boxes together
-            graph.add_edge(a, b, ());
-        }
-        // find all the nets and record their cardinality
-        let mut nets_sizes = Vec::new();
-        let mut visited = HashSet::new(); // we want to only visit each node once
-        for node in graph.node_indices() {
-            if graph.neighbors(node).count() == 0 {
-                // unconnected nodes can be ignored
-                continue;
-            }
-            if visited.contains(&node) {
-                // already visited nodes must be ignored
-                continue;
-            }
-            // visit all connected nodes and record the size of the net
-            let mut visitor = DfsPostOrder::new(&graph, node);
-            let mut cardinality = 0;
-            while let Some(node) = visitor.next(&graph) {
-                visited.insert(node);
-                cardinality += 1;
-            }
-            nets_sizes.push(Reverse(cardinality)); // we want to sort in descending order hence the `Reverse`
+    fn part_1(input: &Self::Input<'_>) -> Self::Output1 {
+        let mut nets = Nets::new(input.len());
+        // iterate over the pairs in ascending distance order up to the number of connections required
+        for (_, a, b) in NearestPairs::new(input).take(NUM_CONNECTIONS) {
+            // link the two junctions boxes together (might already be in the same net)
+            nets.union(a, b);
         }
-        // multiply the size of the 3 largest nets
-        nets_sizes
-            .into_iter()
-            .sorted_unstable()
-            .take(3)
-            .map(|l| l.0)
-            .product()
+        largest_nets_product(&nets)
     }
 
     type Output2 = usize;
 
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        let mut graph = input.clone();
-        // compute all pairs' distances and iterate over them in ascending order
-        // until all nodes are part of a single net
-        let dist = get_all_dist_sorted(&graph);
-        for (_, a, b) in dist {
-            graph.add_edge(a, b, ());
-            // small shortcut here, but we could have done it the same way as part 1
-            if connected_components(&graph) == 1 {
-                // by connecting the last pair, all nodes are connected to each other, we're done!
-                return graph.node_weight(a).unwrap().x * graph.node_weight(b).unwrap().x;
+    fn part_2(input: &Self::Input<'_>) -> Self::Output2 {
+        let mut nets = Nets::new(input.len());
+        // iterate over the pairs in ascending order until all junction boxes are part of a single net
+        for (_, a, b) in NearestPairs::new(input) {
+            if nets.union(a, b) && nets.count == 1 {
+                // by connecting the last pair, all junction boxes are connected to each other, we're done!
+                return input[a].x * input[b].x;
             }
         }
         0
     }
+
+    fn solve_both(input: &Self::Input<'_>) -> (Self::Output1, Self::Output2) {
+        // a single stream of pairs serves both parts
+        let mut nets = Nets::new(input.len());
+        let mut res_1 = None;
+        let mut res_2 = None;
+        for (i, (_, a, b)) in NearestPairs::new(input).enumerate() {
+            if nets.union(a, b) && nets.count == 1 {
+                res_2 = Some(input[a].x * input[b].x);
+            }
+            if i + 1 == NUM_CONNECTIONS {
+                res_1 = Some(largest_nets_product(&nets));
+            }
+            if res_1.is_some() && res_2.is_some() {
+                break;
+            }
+        }
+        (
+            res_1.unwrap_or_else(|| largest_nets_product(&nets)),
+            res_2.unwrap_or_default(),
+        )
+    }
 }
 
 #[cfg(test)]
 #[expect(const_item_mutation)]
 mod tests {
     use super::*;
+    use crate::days::TestRng;
 
     const INPUT: &str = "162,817,812
 57,618,57
@@ -169,4 +487,55 @@ mod tests {
         let parsed = Day08::parser(&mut INPUT).unwrap();
         assert_eq!(Day08::part_2(&parsed), 25_272);
     }
+
+    #[test]
+    fn test_solve_both() {
+        let parsed = Day08::parser(&mut INPUT).unwrap();
+        assert_eq!(Day08::solve_both(&parsed), (40, 25_272));
+    }
+
+    #[test]
+    fn test_dendrogram() {
+        let parsed = Day08::parser(&mut INPUT).unwrap();
+        let dendrogram = Dendrogram::new(&parsed);
+        assert_eq!(dendrogram.merges.len(), parsed.len() - 1);
+        assert_eq!(dendrogram.merges.last().unwrap().size, parsed.len());
+        // same as part 1
+        let sizes = dendrogram.sizes_at_edges(NUM_CONNECTIONS);
+        assert_eq!(sizes[..3], [5, 4, 2]);
+        assert_eq!(dendrogram.sizes_at_distance(0), vec![1; parsed.len()]);
+        assert_eq!(dendrogram.sizes_at_distance(usize::MAX), vec![parsed.len()]);
+    }
+
+    #[test]
+    fn test_newick() {
+        let points = [
+            Point { x: 0, y: 0, z: 0 },
+            Point { x: 1, y: 0, z: 0 },
+            Point { x: 0, y: 5, z: 0 },
+        ];
+        let dendrogram = Dendrogram::new(&points);
+        assert_eq!(dendrogram.to_newick(), "((0:1,1:1):4,2:5);");
+    }
+
+    #[test]
+    fn test_nearest_pairs() {
+        // pseudo-random points with many duplicate coordinates
+        let mut rng = TestRng::new(42);
+        let points: Vec<_> = (0..300)
+            .map(|_| Point {
+                x: rng.below(50),
+                y: rng.below(50),
+                z: rng.below(50),
+            })
+            .collect();
+        // compare with the brute-force list of all pairs
+        let mut expected = Vec::new();
+        for (a, b) in (0..points.len()).tuple_combinations() {
+            expected.push((points[a].dist_squared(&points[b]), a, b));
+        }
+        expected.sort_unstable();
+        let pairs: Vec<_> = NearestPairs::new(&points).collect();
+        assert_eq!(pairs, expected);
+    }
 }
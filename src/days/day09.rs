@@ -1,4 +1,4 @@
-use std::{iter::once, ops::RangeInclusive};
+use std::fmt::Write as _;
 
 use itertools::Itertools;
 use winnow::{
@@ -22,18 +22,103 @@ fn rectangle_area(a: Point, b: Point) -> usize {
     w * h
 }
 
-/// Check if two ranges overlap
-fn range_overlap(a: RangeInclusive<usize>, b: RangeInclusive<usize>) -> bool {
-    if a.start() <= b.start() {
-        a.end() > b.start()
-    } else {
-        b.end() > a.start()
+/// A cell of the compressed grid, as (column, row)
+type Cell = (usize, usize);
+
+/// Index of a coordinate in the compressed grid, whose even indexes are the coordinates of the red tiles and odd
+/// indexes are the gaps between two consecutive ones
+fn compressed(values: &[usize], value: usize) -> Option<usize> {
+    match values.binary_search(&value) {
+        Ok(i) => Some(2 * i),
+        Err(i) if i > 0 && i < values.len() => Some(2 * i - 1),
+        Err(_) => None,
     }
 }
 
-/// Create a range from two numbers (inclusive)
-fn range(a: usize, b: usize) -> RangeInclusive<usize> {
-    if a < b { a..=b } else { b..=a }
+/// Whether a compressed coordinate has any tiles (a gap between two consecutive coordinates could be empty)
+fn has_tiles(values: &[usize], index: usize) -> bool {
+    index.is_multiple_of(2) || values[index / 2 + 1] - values[index / 2] > 1
+}
+
+/// Raster of the polygon on the compressed grid, with 2D prefix sums to count the cells outside of the polygon
+///
+/// All the tiles in a compressed cell are either inside or outside the polygon, since no edge goes through the gaps.
+struct Raster {
+    xs: Vec<usize>,
+    ys: Vec<usize>,
+    /// `outside[y][x]` is the number of (non-empty) cells outside the polygon with a row below `y` and a column below
+    /// `x`
+    outside: Vec<Vec<usize>>,
+}
+
+impl Raster {
+    fn new(points: &[Point]) -> Self {
+        let xs: Vec<_> = points
+            .iter()
+            .map(|p| p.x)
+            .sorted_unstable()
+            .dedup()
+            .collect();
+        let ys: Vec<_> = points
+            .iter()
+            .map(|p| p.y)
+            .sorted_unstable()
+            .dedup()
+            .collect();
+        let (width, height) = (2 * xs.len() - 1, 2 * ys.len() - 1);
+        let mut raster = Self {
+            xs,
+            ys,
+            outside: vec![vec![0; width + 1]; height + 1],
+        };
+        let edges: Vec<(Cell, Cell)> = points
+            .iter()
+            .circular_tuple_windows()
+            .map(|(a, b)| (raster.cell(*a).unwrap(), raster.cell(*b).unwrap()))
+            .collect();
+        let mut boundary = vec![vec![false; width]; height];
+        for ((ax, ay), (bx, by)) in &edges {
+            for row in &mut boundary[*ay.min(by)..=*ay.max(by)] {
+                row[*ax.min(bx)..=*ax.max(bx)].fill(true);
+            }
+        }
+        for (y, boundary) in boundary.iter().enumerate() {
+            // cast a ray from the left of the row: a cell is inside if it crossed an odd number of vertical edges,
+            // where an edge covers the half-open range of rows from its top end (which avoids counting the corners
+            // twice)
+            let mut crossings = vec![false; width];
+            for ((ax, ay), (_, by)) in &edges {
+                if *ay.min(by) <= y && y < *ay.max(by) && ax + 1 < width {
+                    crossings[ax + 1] ^= true;
+                }
+            }
+            let mut inside = false;
+            for x in 0..width {
+                inside ^= crossings[x];
+                let outside =
+                    !inside && !boundary[x] && has_tiles(&raster.xs, x) && has_tiles(&raster.ys, y);
+                raster.outside[y + 1][x + 1] =
+                    usize::from(outside) + raster.outside[y][x + 1] + raster.outside[y + 1][x]
+                        - raster.outside[y][x];
+            }
+        }
+        raster
+    }
+
+    /// Find the cell containing a tile, if it is within the bounding box of the polygon
+    fn cell(&self, point: Point) -> Option<Cell> {
+        Some((
+            compressed(&self.xs, point.x)?,
+            compressed(&self.ys, point.y)?,
+        ))
+    }
+
+    /// Check if the rectangle between two cells (inclusive) is fully inside the polygon
+    fn contains(&self, (ax, ay): Cell, (bx, by): Cell) -> bool {
+        let (x0, x1) = (ax.min(bx), ax.max(bx) + 1);
+        let (y0, y1) = (ay.min(by), ay.max(by) + 1);
+        self.outside[y1][x1] + self.outside[y0][x0] == self.outside[y0][x1] + self.outside[y1][x0]
+    }
 }
 
 pub struct Day09;
@@ -45,77 +130,155 @@ fn parse_point(input: &mut &str) -> Result<Point> {
 }
 
 impl Day for Day09 {
-    type Input = Vec<Point>;
+    type Input<'a> = Vec<Point>;
 
-    fn parser(input: &mut &str) -> Result<Self::Input> {
+    fn parser<'a>(input: &mut &'a str) -> Result<Self::Input<'a>> {
         separated(1.., parse_point, newline).parse_next(input)
     }
 
     type Output1 = usize;
 
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        input
-            .iter()
-            .combinations_with_replacement(2)
-            .map(|points| {
-                let [a, b, ..] = points.as_slice() else {
-                    unreachable!();
-                };
-                rectangle_area(**a, **b)
-            })
-            .max()
-            .unwrap()
+    fn part_1(input: &Self::Input<'_>) -> Self::Output1 {
+        let (a, b) = largest_rectangle(input);
+        rectangle_area(a, b)
     }
 
     type Output2 = usize;
 
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        let first_point = input.iter().next().unwrap();
-        let edges: Vec<(Point, Point)> = input
+    fn part_2(input: &Self::Input<'_>) -> Self::Output2 {
+        largest_inside_rectangle(input).map_or(0, |(a, b)| rectangle_area(a, b))
+    }
+}
+
+/// Find the opposite corners of the largest rectangle between two red tiles
+fn largest_rectangle(points: &[Point]) -> (Point, Point) {
+    points
+        .iter()
+        .combinations_with_replacement(2)
+        .map(|corners| (*corners[0], *corners[1]))
+        .max_by_key(|(a, b)| rectangle_area(*a, *b))
+        .unwrap()
+}
+
+/// Find the opposite corners of the largest rectangle between two red tiles which is fully inside the polygon
+fn largest_inside_rectangle(points: &[Point]) -> Option<(Point, Point)> {
+    let raster = Raster::new(points);
+    let cells: Vec<_> = points.iter().map(|p| raster.cell(*p).unwrap()).collect();
+    points
+        .iter()
+        .zip(&cells)
+        .tuple_combinations()
+        .filter(|((_, a), (_, b))| raster.contains(**a, **b))
+        .map(|((a, _), (b, _))| (*a, *b))
+        .max_by_key(|(a, b)| rectangle_area(*a, *b))
+}
+
+/// Draw the polygon as an SVG image, with the largest rectangles of both parts and their corners
+///
+/// The tiles are scaled to fit the image, since the inputs span about 100k tiles.
+#[expect(clippy::cast_precision_loss)]
+pub fn to_svg(points: &[Point]) -> String {
+    const SIZE: f64 = 800.;
+    const MARGIN: f64 = 60.;
+    let (min_x, max_x) = points.iter().map(|p| p.x).minmax().into_option().unwrap();
+    let (min_y, max_y) = points.iter().map(|p| p.y).minmax().into_option().unwrap();
+    let scale = SIZE / (max_x - min_x).max(max_y - min_y).max(1) as f64;
+    let x = |x: usize| (x - min_x) as f64 * scale + MARGIN;
+    let y = |y: usize| (y - min_y) as f64 * scale + MARGIN;
+    let (width, height) = (x(max_x) + MARGIN, y(max_y) + MARGIN);
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width:.0}\" height=\"{height:.0}\""
+    );
+    writeln!(
+        svg,
+        " viewBox=\"0 0 {width:.0} {height:.0}\" font-family=\"monospace\" font-size=\"12\">"
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        "  <polygon points=\"{}\" fill=\"#8fd18f\" stroke=\"#d02020\" stroke-width=\"1.5\"/>",
+        points
             .iter()
-            .chain(once(first_point))
-            .copied()
-            .tuple_windows()
-            .collect();
-        let mut max_area = 0;
-        for corners in input.iter().combinations_with_replacement(2) {
-            let [a, b, ..] = corners.as_slice() else {
-                unreachable!();
-            };
-            if a == b {
-                continue; // not a rectangle
-            }
-            let area = rectangle_area(**a, **b);
-            // only needed to check the edges if the rectangle is actually a better candidate that previously seen
-            if area <= max_area {
-                continue;
-            }
-            // check if any edge crosses rectangle (one end of the edge is fully inside the rectangle)
-            if edges.iter().any(|(e, f)| {
-                if e.x == f.x {
-                    // vertical edge
-                    e.x > a.x.min(b.x)
-                        && e.x < a.x.max(b.x)
-                        && range_overlap(range(e.y, f.y), range(a.y, b.y))
-                } else {
-                    // horizontal edge
-                    e.y > a.y.min(b.y)
-                        && e.y < a.y.max(b.y)
-                        && range_overlap(range(e.x, f.x), range(a.x, b.x))
-                }
-            }) {
-                continue;
-            }
-            max_area = area;
+            .map(|p| format!("{:.1},{:.1}", x(p.x), y(p.y)))
+            .join(" ")
+    )
+    .unwrap();
+    for p in points {
+        writeln!(
+            svg,
+            "  <circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"2\" fill=\"#d02020\"/>",
+            x(p.x),
+            y(p.y)
+        )
+        .unwrap();
+    }
+    let rectangles = [
+        ("part 1", Some(largest_rectangle(points)), "#2060d0", "none"),
+        (
+            "part 2",
+            largest_inside_rectangle(points),
+            "#e08000",
+            "#e08000",
+        ),
+    ];
+    for (i, (part, corners, stroke, fill)) in rectangles.into_iter().enumerate() {
+        let Some((a, b)) = corners else {
+            continue;
+        };
+        // legend at the top, with the area of the rectangle
+        writeln!(
+            svg,
+            "  <text x=\"{MARGIN}\" y=\"{}\" fill=\"{stroke}\">{part}: {}</text>",
+            20 + 16 * i,
+            rectangle_area(a, b)
+        )
+        .unwrap();
+        let (left, right) = (x(a.x.min(b.x)), x(a.x.max(b.x)));
+        let (top, bottom) = (y(a.y.min(b.y)), y(a.y.max(b.y)));
+        write!(
+            svg,
+            "  <rect x=\"{left:.1}\" y=\"{top:.1}\" width=\"{:.1}\" height=\"{:.1}\"",
+            right - left,
+            bottom - top,
+        )
+        .unwrap();
+        write!(
+            svg,
+            " fill=\"{fill}\" stroke=\"{stroke}\" stroke-width=\"2\" fill-opacity=\"0.25\" stroke-dasharray=\"6 3\">"
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            "<title>{part}: {}</title></rect>",
+            rectangle_area(a, b)
+        )
+        .unwrap();
+        // part 1's corners are labelled above them, and part 2's below, so that they don't overlap when shared
+        let offset = if i == 0 { -4. } else { 14. };
+        for corner in [a, b] {
+            writeln!(
+                svg,
+                "  <text x=\"{:.1}\" y=\"{:.1}\" fill=\"{stroke}\">{},{}</text>",
+                x(corner.x) + 4.,
+                y(corner.y) + offset,
+                corner.x,
+                corner.y
+            )
+            .unwrap();
         }
-        max_area
     }
+    svg.push_str("</svg>\n");
+    svg
 }
 
 #[cfg(test)]
 #[expect(const_item_mutation)]
 mod tests {
+    use std::collections::HashSet;
+
     use super::*;
+    use crate::days::TestRng;
 
     const INPUT: &str = "7,1
 11,1
@@ -137,4 +300,109 @@ mod tests {
         let parsed = Day09::parser(&mut INPUT).unwrap();
         assert_eq!(Day09::part_2(&parsed), 24);
     }
+
+    #[test]
+    fn test_concave() {
+        // a U shape: no edge crosses the rectangle between the inner corners of the notch, yet it's outside the polygon
+        let parsed = Day09::parse("0,0\n2,0\n2,4\n8,4\n8,0\n10,0\n10,6\n0,6").unwrap();
+        assert_eq!(Day09::part_2(&parsed), 27);
+    }
+
+    /// Tiles of the polygon (boundary or interior), checking every point of the bounding box
+    fn brute_force_tiles(points: &[Point]) -> HashSet<Point> {
+        let edges: Vec<_> = points.iter().circular_tuple_windows().collect();
+        let (x_max, y_max) = (
+            points.iter().map(|p| p.x).max().unwrap(),
+            points.iter().map(|p| p.y).max().unwrap(),
+        );
+        (0..=x_max)
+            .cartesian_product(0..=y_max)
+            .map(|(x, y)| Point { x, y })
+            .filter(|p| {
+                let on_edge = edges.iter().any(|(a, b)| {
+                    (a.x.min(b.x)..=a.x.max(b.x)).contains(&p.x)
+                        && (a.y.min(b.y)..=a.y.max(b.y)).contains(&p.y)
+                });
+                let crossings = edges
+                    .iter()
+                    .filter(|(a, b)| {
+                        a.x == b.x && a.x < p.x && a.y.min(b.y) <= p.y && p.y < a.y.max(b.y)
+                    })
+                    .count();
+                on_edge || crossings % 2 == 1
+            })
+            .collect()
+    }
+
+    /// Generate an orthogonal polygon made of columns of random widths, with notches on both sides
+    fn random_polygon(rng: &mut TestRng) -> Vec<Point> {
+        let columns = 2 + rng.below(6);
+        let mut xs = vec![0];
+        let mut spans: Vec<(usize, usize)> = vec![];
+        while spans.len() < columns {
+            let low = rng.below(6);
+            let span = (low, low + 1 + rng.below(6));
+            // neighbouring columns must overlap, so that the outline doesn't touch itself
+            if spans
+                .last()
+                .is_none_or(|last| last.0.max(span.0) < last.1.min(span.1))
+            {
+                spans.push(span);
+                xs.push(xs.last().unwrap() + 1 + rng.below(3));
+            }
+        }
+        let top = (0..columns).flat_map(|i| [(xs[i], spans[i].1), (xs[i + 1], spans[i].1)]);
+        let bottom = (0..columns)
+            .rev()
+            .flat_map(|i| [(xs[i + 1], spans[i].0), (xs[i], spans[i].0)]);
+        let transpose = rng.below(2) == 1;
+        top.chain(bottom)
+            .dedup()
+            .map(|(x, y)| {
+                if transpose {
+                    Point { x: y, y: x }
+                } else {
+                    Point { x, y }
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_raster() {
+        let mut rng = TestRng::new(9);
+        for _ in 0..200 {
+            let points = random_polygon(&mut rng);
+            let tiles = brute_force_tiles(&points);
+            let raster = Raster::new(&points);
+            let mut best = 0;
+            for (a, b) in points.iter().tuple_combinations() {
+                let inside = (a.x.min(b.x)..=a.x.max(b.x))
+                    .cartesian_product(a.y.min(b.y)..=a.y.max(b.y))
+                    .all(|(x, y)| tiles.contains(&Point { x, y }));
+                let cells = (raster.cell(*a).unwrap(), raster.cell(*b).unwrap());
+                assert_eq!(
+                    raster.contains(cells.0, cells.1),
+                    inside,
+                    "{points:?} {a:?} {b:?}"
+                );
+                if inside {
+                    best = best.max(rectangle_area(*a, *b));
+                }
+            }
+            assert_eq!(Day09::part_2(&points), best, "{points:?}");
+        }
+    }
+
+    #[test]
+    fn test_svg() {
+        let parsed = Day09::parse(INPUT).unwrap();
+        let svg = to_svg(&parsed);
+        assert_eq!(svg.matches("<circle ").count(), 8);
+        assert!(svg.contains(">part 1: 50<"));
+        assert!(svg.contains(">part 2: 24<"));
+        // part 2's rectangle goes from 2,3 to 9,5
+        assert!(svg.contains(">9,5</text>"));
+        assert_eq!(svg.matches(">2,3</text>").count(), 2);
+    }
 }
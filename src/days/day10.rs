@@ -1,31 +1,102 @@
 use std::{
-    cmp::Reverse,
-    collections::{BinaryHeap, HashMap, VecDeque},
-    ops::{Deref, DerefMut},
+    fmt::Display,
+    ops::{BitXorAssign, Deref},
 };
 
 use good_lp::{
     Expression, Solution, SolverModel, Variable, constraint, default_solver, variable, variables,
 };
+use itertools::Itertools as _;
+use num::{BigInt, BigRational, Integer, One as _, ToPrimitive as _, Zero as _};
 use winnow::{
     Parser, Result,
     ascii::{dec_uint, newline},
-    combinator::{delimited, repeat, separated, seq},
+    combinator::{delimited, opt, repeat, separated, seq},
     token::one_of,
 };
 
 use crate::days::Day;
 
+/// The largest dimension of the null space [`solve_lights`] enumerates, as it tries `2^n` button combinations
+const MAX_FREE_BUTTONS: usize = 20;
+
+/// A growable bitset, backing [`Lights`] and [`Button`] as well as the sets of pressed buttons
+///
+/// Trailing zero words are always trimmed, so that equal sets have the same representation.
+#[derive(Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Bits(Vec<u64>);
+
+impl Bits {
+    /// Check whether bit `i` is set
+    fn contains(&self, i: usize) -> bool {
+        self.0
+            .get(i / 64)
+            .is_some_and(|word| (word >> (i % 64)) & 1 == 1)
+    }
+
+    /// Set bit `i`
+    fn insert(&mut self, i: usize) {
+        if self.0.len() <= i / 64 {
+            self.0.resize(i / 64 + 1, 0);
+        }
+        self.0[i / 64] |= 1 << (i % 64);
+    }
+
+    /// One past the highest bit which can be set
+    fn width(&self) -> usize {
+        self.0.len() * 64
+    }
+
+    fn count_ones(&self) -> u32 {
+        self.0.iter().map(|word| word.count_ones()).sum()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterate over the positions of the set bits, in increasing order
+    fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.width()).filter(|i| self.contains(*i))
+    }
+}
+
+impl BitXorAssign<&Bits> for Bits {
+    fn bitxor_assign(&mut self, rhs: &Bits) {
+        if self.0.len() < rhs.0.len() {
+            self.0.resize(rhs.0.len(), 0);
+        }
+        for (word, other) in self.0.iter_mut().zip(&rhs.0) {
+            *word ^= other;
+        }
+        while self.0.last() == Some(&0) {
+            self.0.pop();
+        }
+    }
+}
+
+impl FromIterator<usize> for Bits {
+    fn from_iter<T: IntoIterator<Item = usize>>(iter: T) -> Self {
+        let mut bits = Self::default();
+        for i in iter {
+            bits.insert(i);
+        }
+        bits
+    }
+}
+
 /// A compact representation of the lights state for a machine
 ///
 /// Each bit represents one light, with the LSB being the left-most light in the input.
-#[derive(Copy, Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
-pub struct Lights(u16);
+#[derive(Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Lights(Bits);
 
 impl std::fmt::Debug for Lights {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "[")?;
-        write!(f, "{:b}", self.0)?;
+        for i in 0..self.width() {
+            write!(f, "{}", if self.contains(i) { '#' } else { '.' })?;
+        }
         write!(f, "]")
     }
 }
@@ -33,22 +104,26 @@ impl std::fmt::Debug for Lights {
 /// A compact representation of a button (which lights it toggles)
 ///
 /// Each bit corresponds to one light, with the LSB being the left-most light in the input.
-#[derive(Copy, Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
-pub struct Button(u16);
+#[derive(Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Button(Bits);
 
 impl std::fmt::Debug for Button {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "(")?;
-        for b in 0..16 {
-            let mask = 1 << b;
-            if self.0 & mask > 0 {
-                write!(f, "{b},")?;
-            }
+        for b in self.ones() {
+            write!(f, "{b},")?;
         }
         write!(f, ")")
     }
 }
 
+impl Display for Button {
+    /// Display the list of lights toggled by the button, as in the input
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({})", self.ones().join(","))
+    }
+}
+
 /// A machine definition, with the required end state for the lights and the available buttons + required joltages
 #[derive(Debug, Clone, Hash)]
 pub struct Machine {
@@ -59,140 +134,752 @@ pub struct Machine {
 
 impl Lights {
     /// Press a button (toggle some lights)
-    fn press_button(self, button: Button) -> Self {
-        Lights(*self ^ *button) // noice
+    fn press_button(mut self, button: &Button) -> Self {
+        self.0 ^= &button.0; // noice
+        self
     }
 }
 
 impl From<&[bool]> for Lights {
     /// Construct the compact representation from a list of booleans
     fn from(value: &[bool]) -> Self {
-        let mut bits = 0;
-        for (i, bit) in value.iter().enumerate() {
-            if *bit {
-                bits |= 1 << i;
-            }
-        }
-        Self(bits)
+        Self(
+            value
+                .iter()
+                .enumerate()
+                .filter_map(|(i, bit)| bit.then_some(i))
+                .collect(),
+        )
     }
 }
 
-impl From<&[u8]> for Button {
+impl From<&[usize]> for Button {
     /// Construct the compact representation from a list of light positions
-    fn from(value: &[u8]) -> Self {
-        let mut bits = 0;
-        for bit in value {
-            bits |= 1 << bit;
-        }
-        Self(bits)
+    fn from(value: &[usize]) -> Self {
+        Self(value.iter().copied().collect())
     }
 }
 
-/// Reconstruct the path (which buttons were pressed in which order) for A*
+/// Find the smallest set of buttons to press to reach the target lights state, starting with all lights off
 ///
-/// Not actually used but kept for reference
-#[allow(unused)]
-fn path(came_from: &HashMap<Lights, Button>, current: Lights) -> VecDeque<Button> {
-    let mut path: VecDeque<Button> = VecDeque::new();
-    let mut current = current;
-    while came_from.contains_key(&current) {
-        let button = *came_from.get(&current).unwrap();
-        current = current.press_button(button);
-        path.push_front(button);
-    }
-    path
-}
-
-/// Search the shortest sequence of button presses which leads to the target lights state with A*
-fn a_star(buttons: &[Button], start: Lights, target: Lights) -> Option<usize> {
-    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-    struct Candidate {
-        cost: Reverse<usize>,
-        state: Lights,
-    }
-    let mut open_set = BinaryHeap::<Candidate>::new(); // min-heap thanks to Reverse
-    open_set.push(Candidate {
-        cost: Reverse(1),
-        state: start,
-    });
-    // let mut came_from = HashMap::<Lights, Button>::new(); // if we need to find back the path taken
-    let mut dist = HashMap::<Lights, usize>::new(); // traveled distance (number of buttons pressed)
-    dist.insert(start, 0);
-
-    while let Some(current) = open_set.pop() {
-        if current.state == target {
-            // we could get the path back if we wanted (not needed here)
-            // let path = path(&came_from, current.state);
-            return dist.get(&current.state).copied();
-        }
-
-        for button in buttons {
-            let n = current.state.press_button(*button);
-            // total number of button pressed if we pressed this button
-            let tentative_dist = dist.get(&current.state).unwrap() + 1;
-            if tentative_dist < *dist.get(&n).unwrap_or(&usize::MAX) {
-                // came_from.insert(n, *button); // if path is needed, we record it here
-                dist.insert(n, tentative_dist);
-                // save neighbour as candidate
-                // the heuristic must not overestimate the cost of reaching the target,
-                // so we use the minimum possible number of button presses (1)
-                let candidate = Candidate {
-                    cost: Reverse(tentative_dist + 1),
-                    state: n,
-                };
-                // replace this state in the min-heap
-                open_set.retain(|c| c.state != candidate.state);
-                open_set.push(candidate);
+/// Pressing a button twice cancels out and the order of presses doesn't matter, so we're looking for a subset of
+/// buttons whose XOR equals the target. This is a linear system over GF(2), with one equation per light and one unknown
+/// per button. We solve it with Gaussian elimination, then enumerate its null space to find the solution with the
+/// fewest buttons pressed. That's `2^n` combinations for `n` free buttons, so machines with more than
+/// [`MAX_FREE_BUTTONS`] are rejected rather than taking forever.
+///
+/// Returns the set of buttons to press (bit `i` for the `i`-th button).
+fn solve_lights(buttons: &[Button], target: &Lights) -> std::result::Result<Bits, SolveError> {
+    let n_lights = buttons
+        .iter()
+        .map(|b| b.width())
+        .fold(target.width(), usize::max);
+    // one row per light: which buttons toggle it, and whether it should end up on
+    let mut rows: Vec<(Bits, bool)> = (0..n_lights)
+        .map(|light| {
+            let mask = buttons
+                .iter()
+                .enumerate()
+                .filter_map(|(i, b)| b.contains(light).then_some(i))
+                .collect();
+            (mask, target.contains(light))
+        })
+        .collect();
+    // reduce to row echelon form (fully reduced, so that each pivot column only has one non-zero entry)
+    let mut pivots = Vec::new(); // pivot column for each of the first rows
+    for col in 0..buttons.len() {
+        let r = pivots.len();
+        let Some(found) = (r..rows.len()).find(|i| rows[*i].0.contains(col)) else {
+            continue; // free variable
+        };
+        rows.swap(r, found);
+        let pivot = rows[r].clone();
+        for (i, row) in rows.iter_mut().enumerate() {
+            if i != r && row.0.contains(col) {
+                row.0 ^= &pivot.0;
+                row.1 ^= pivot.1;
             }
         }
+        pivots.push(col);
+    }
+    // a row with no buttons which still needs toggling means the system is inconsistent
+    if rows[pivots.len()..]
+        .iter()
+        .any(|(mask, rhs)| mask.is_empty() && *rhs)
+    {
+        return Err(SolveError::Unreachable);
+    }
+    if buttons.len() - pivots.len() > MAX_FREE_BUTTONS {
+        return Err(SolveError::TooLarge);
+    }
+    // particular solution, with all free variables set to zero
+    let particular: Bits = pivots
+        .iter()
+        .zip(&rows)
+        .filter_map(|(col, (_, rhs))| rhs.then_some(*col))
+        .collect();
+    // basis of the null space: setting one free variable determines the pivot variables
+    let null_space: Vec<Bits> = (0..buttons.len())
+        .filter(|col| !pivots.contains(col))
+        .map(|free| {
+            pivots
+                .iter()
+                .zip(&rows)
+                .filter_map(|(col, (mask, _))| mask.contains(free).then_some(*col))
+                .chain([free])
+                .collect()
+        })
+        .collect();
+    // enumerate all solutions in Gray code order (one basis vector changes at each step)
+    let mut best = particular.clone();
+    let mut current = particular;
+    for i in 1u64..1 << null_space.len() {
+        current ^= &null_space[i.trailing_zeros() as usize];
+        if current.count_ones() < best.count_ones() {
+            best = current.clone();
+        }
+    }
+    Ok(best)
+}
+
+/// The total number of button presses for all machines, unless one of them can't reach its target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Presses {
+    Total(usize),
+    /// The index of the first machine for which the target is unreachable
+    Unreachable(usize),
+    /// The index of the first machine for which the solver returned a wrong solution
+    Invalid(usize),
+    /// The index of the first machine too large to solve
+    TooLarge(usize),
+}
+
+impl Display for Presses {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Presses::Total(n) => write!(f, "{n}"),
+            Presses::Unreachable(i) => write!(f, "unreachable (machine {})", i + 1),
+            Presses::Invalid(i) => write!(f, "invalid solution (machine {})", i + 1),
+            Presses::TooLarge(i) => write!(f, "too large to solve (machine {})", i + 1),
+        }
     }
-    None
 }
 
 /// Retrieve the list of which buttons (index) increment a given joltage (identified by its position in the list)
 fn buttons_idx_for_joltage(i: usize, buttons: &[Button]) -> Vec<usize> {
-    let mask = 1u16 << i;
     buttons
         .iter()
         .enumerate()
-        .filter_map(|(i, b)| (**b & mask > 0).then_some(i))
+        .filter_map(|(b, button)| button.contains(i).then_some(b))
         .collect()
 }
 
 /// Identify the maximum number of possible button presses before we exceed any of the desired joltages
-fn max_button_presses(mut button: Button, joltages: &[u16]) -> u16 {
-    let mut min = u16::MAX;
-    let mut b = 0;
-    while *button > 0 {
-        if button.trailing_ones() > 0 && joltages[b] < min {
-            min = joltages[b];
+fn max_button_presses(button: &Button, joltages: &[u16]) -> u16 {
+    button.ones().map(|b| joltages[b]).min().unwrap_or(u16::MAX)
+}
+
+/// Which solver to use for the joltages (part 2)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoltageSolver {
+    /// Exact integer solver implemented in this crate
+    Exact,
+    /// Integer linear programming with `good_lp`
+    Lp,
+}
+
+impl JoltageSolver {
+    /// Select the solver with the `DAY10_SOLVER` environment variable (`exact` by default, or `lp`)
+    fn from_env() -> Self {
+        match std::env::var("DAY10_SOLVER").as_deref() {
+            Ok("lp") => Self::Lp,
+            _ => Self::Exact,
+        }
+    }
+
+    /// Find the number of presses for each button which gives the required joltages with the fewest presses in total
+    fn solve(self, machine: &Machine) -> std::result::Result<Vec<u64>, SolveError> {
+        match self {
+            Self::Exact => solve_joltages(machine),
+            Self::Lp => solve_joltages_lp(machine).ok_or(SolveError::Unreachable),
+        }
+    }
+}
+
+/// Why the buttons to press for a machine couldn't be found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SolveError {
+    /// No combination of presses gives the lights or joltages
+    Unreachable,
+    /// The lights have too many free buttons to enumerate, or the reduced joltages system has coefficients too large
+    /// for the exact solver's 64-bit integers
+    TooLarge,
+}
+
+/// Find the number of presses for each button which gives the required joltages with the fewest presses in total
+///
+/// Each joltage is the sum of the presses of the buttons connected to it, which gives a system of linear equations with
+/// one unknown per button. We reduce it with exact rational Gaussian elimination: the pivot variables are then fully
+/// determined by the free variables. Finally, we search all values of the free variables (bounded by
+/// [`max_button_presses`]) for the solution where all presses are non-negative integers and their sum is minimal. The
+/// values which can't beat the best solution so far, even with fractional presses for the other buttons, are pruned
+/// (see [`relaxed_min_presses`]).
+///
+/// The elimination uses big rationals, so it can't overflow. The reduced equations are scaled to integers which must
+/// fit in 64 bits, and the search works with 128-bit integers: the sums of products with the 16-bit press counts can't
+/// overflow them.
+fn solve_joltages(machine: &Machine) -> std::result::Result<Vec<u64>, SolveError> {
+    let n_buttons = machine.buttons.len();
+    let bounds: Vec<i128> = machine
+        .buttons
+        .iter()
+        .map(|b| i128::from(max_button_presses(b, &machine.joltages)))
+        .collect();
+    let (equations, free) = reduce_joltages(machine)?;
+    // the objective (total presses) is an affine function of the free variables, only their coefficients matter to try
+    // the values lowering it first (so rounding errors don't matter)
+    #[expect(clippy::cast_precision_loss)]
+    let weights = (0..free.len())
+        .map(|f| {
+            1. - equations
+                .iter()
+                .map(|eq| eq.coefs[f] as f64 / eq.denom as f64)
+                .sum::<f64>()
+        })
+        .collect();
+    let buttons_joltages: Vec<Vec<usize>> = machine
+        .buttons
+        .iter()
+        .map(|b| {
+            (0..machine.joltages.len())
+                .filter(|i| b.contains(*i))
+                .collect()
+        })
+        .collect();
+    let mut search = FreeSearch {
+        free_bounds: free.iter().map(|f| bounds[*f]).collect(),
+        free_joltages: free.iter().map(|f| buttons_joltages[*f].clone()).collect(),
+        pivot_joltages: equations
+            .iter()
+            .map(|eq| buttons_joltages[eq.col].clone())
+            .collect(),
+        pivot_bounds: equations.iter().map(|eq| bounds[eq.col]).collect(),
+        equations: &equations,
+        weights,
+        values: vec![0; free.len()],
+        remaining: machine.joltages.iter().copied().map(i128::from).collect(),
+        best: None,
+    };
+    search.run(0);
+    let (_, values) = search.best.ok_or(SolveError::Unreachable)?;
+    // reconstruct all presses from the free variables
+    let mut presses = vec![0; n_buttons];
+    for (f, v) in free.iter().zip(&values) {
+        presses[*f] = *v as u64;
+    }
+    for eq in &equations {
+        presses[eq.col] = eq.solve(&values).ok_or(SolveError::Unreachable)? as u64;
+    }
+    Ok(presses)
+}
+
+/// Reduce the joltages' system with Gaussian elimination, into one equation per pivot variable
+///
+/// Returns the equations, and the free variables (button indices).
+fn reduce_joltages(
+    machine: &Machine,
+) -> std::result::Result<(Vec<PivotEquation>, Vec<usize>), SolveError> {
+    let n_buttons = machine.buttons.len();
+    // augmented matrix, one row per joltage: which buttons increment it, followed by the joltage value
+    let mut rows: Vec<Vec<BigRational>> = machine
+        .joltages
+        .iter()
+        .enumerate()
+        .map(|(i, jolt)| {
+            let mut row = vec![BigRational::zero(); n_buttons + 1];
+            for b in buttons_idx_for_joltage(i, &machine.buttons) {
+                row[b] = BigRational::one();
+            }
+            row[n_buttons] = BigRational::from_integer((*jolt).into());
+            row
+        })
+        .collect();
+    // reduced row echelon form
+    let mut pivots = Vec::new(); // pivot column for each of the first rows
+    for col in 0..n_buttons {
+        let r = pivots.len();
+        let Some(found) = (r..rows.len()).find(|i| !rows[*i][col].is_zero()) else {
+            continue; // free variable
+        };
+        rows.swap(r, found);
+        let pivot = rows[r][col].clone();
+        for v in &mut rows[r] {
+            *v /= &pivot;
+        }
+        let pivot_row = rows[r].clone();
+        for (i, row) in rows.iter_mut().enumerate() {
+            let factor = row[col].clone();
+            if i == r || factor.is_zero() {
+                continue;
+            }
+            for (v, p) in row.iter_mut().zip(&pivot_row) {
+                *v -= p * &factor;
+            }
+        }
+        pivots.push(col);
+    }
+    // a row without any button but a non-zero joltage means there is no solution
+    if rows[pivots.len()..]
+        .iter()
+        .any(|row| !row[n_buttons].is_zero())
+    {
+        return Err(SolveError::Unreachable);
+    }
+    let free: Vec<usize> = (0..n_buttons).filter(|c| !pivots.contains(c)).collect();
+    // scale each pivot row to integers: `denom * x_pivot + sum(coefs[f] * x_free[f]) = rhs`
+    let to_int = |v: BigRational| v.to_integer().to_i64().map(i128::from);
+    let equations = pivots
+        .iter()
+        .zip(&rows)
+        .map(|(col, row)| {
+            let denom = row.iter().fold(BigInt::one(), |acc, v| acc.lcm(v.denom()));
+            let scale = |v: &BigRational| to_int(v * &denom);
+            Some(PivotEquation {
+                col: *col,
+                denom: to_int(BigRational::from_integer(denom.clone()))?,
+                coefs: free
+                    .iter()
+                    .map(|f| scale(&row[*f]))
+                    .collect::<Option<_>>()?,
+                rhs: scale(&row[n_buttons])?,
+            })
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or(SolveError::TooLarge)?;
+    Ok((equations, free))
+}
+
+/// A reduced equation giving the value of a pivot variable from the free variables, scaled to integers
+#[derive(Debug, Clone)]
+struct PivotEquation {
+    /// The pivot column (button index)
+    col: usize,
+    /// The (positive) coefficient of the pivot variable
+    denom: i128,
+    /// Coefficients of the free variables
+    coefs: Vec<i128>,
+    rhs: i128,
+}
+
+impl PivotEquation {
+    /// Compute the pivot variable, if it's an integer
+    fn solve(&self, free_values: &[i128]) -> Option<i128> {
+        let num = self.rhs
+            - self
+                .coefs
+                .iter()
+                .zip(free_values)
+                .map(|(c, v)| c * v)
+                .sum::<i128>();
+        (num % self.denom == 0).then_some(num / self.denom)
+    }
+
+    /// Find the range of values for the free variable at index `depth` which can keep the pivot variable within
+    /// `0..=bound`, given the values of the previous free variables and the bounds of the next ones
+    fn free_range(
+        &self,
+        free_values: &[i128],
+        depth: usize,
+        free_bounds: &[i128],
+        bound: i128,
+    ) -> (i128, i128) {
+        let coef = self.coefs[depth];
+        if coef == 0 {
+            return (i128::MIN, i128::MAX);
+        }
+        // remaining right-hand side once the previous free variables are set
+        let rhs = self.rhs
+            - self.coefs[..depth]
+                .iter()
+                .zip(free_values)
+                .map(|(c, v)| c * v)
+                .sum::<i128>();
+        // range of the contribution of the next free variables
+        let (next_min, next_max) = self.coefs[depth + 1..]
+            .iter()
+            .zip(&free_bounds[depth + 1..])
+            .fold((0, 0), |(min, max), (c, b)| {
+                if *c > 0 {
+                    (min, max + c * b)
+                } else {
+                    (min + c * b, max)
+                }
+            });
+        // we need `0 <= rhs - coef * x - next <= bound * denom` for some `next` in range
+        let hi = rhs - next_min;
+        let lo = rhs - bound * self.denom - next_max;
+        if coef > 0 {
+            (
+                Integer::div_ceil(&lo, &coef),
+                Integer::div_floor(&hi, &coef),
+            )
+        } else {
+            (
+                Integer::div_ceil(&hi, &coef),
+                Integer::div_floor(&lo, &coef),
+            )
+        }
+    }
+}
+
+/// Lower bound of the presses giving the `joltages` with the buttons incrementing the given sets of joltages, from the
+/// linear relaxation of the problem (presses can be fractional), or `None` if even that has no solution
+///
+/// This is the minimum of the sum of presses `x` under `A x = joltages` and `x >= 0`, which we find with the simplex
+/// algorithm: a first phase finds a feasible solution by minimising artificial variables added to each equation, then
+/// a second phase minimises the presses. Bland's rule (entering the first improving column) ensures termination. This
+/// is only used to prune the search, so the floating point rounding is accounted for by the callers.
+#[expect(clippy::cast_precision_loss)] // the joltages are 16-bit
+fn relaxed_min_presses(buttons: &[&[usize]], joltages: &[i128]) -> Option<f64> {
+    const EPSILON: f64 = 1e-9;
+    let (n_rows, n_buttons) = (joltages.len(), buttons.len());
+    // tableau with one row per joltage: the buttons, then the artificial variables, then the right-hand side
+    let n_cols = n_buttons + n_rows;
+    let mut tableau = vec![vec![0.; n_cols + 1]; n_rows];
+    for (b, button) in buttons.iter().enumerate() {
+        for i in *button {
+            tableau[*i][b] = 1.;
+        }
+    }
+    for (i, row) in tableau.iter_mut().enumerate() {
+        row[n_buttons + i] = 1.;
+        row[n_cols] = joltages[i] as f64;
+    }
+    let mut basis: Vec<usize> = (n_buttons..n_cols).collect();
+    // minimise the artificial variables, then the presses without letting the artificial variables enter the basis
+    for phase in [1, 2] {
+        let cost = |col: usize| match phase {
+            1 => f64::from(u8::from(col >= n_buttons)),
+            _ => f64::from(u8::from(col < n_buttons)),
+        };
+        loop {
+            let reduced_cost = |col: usize| {
+                cost(col)
+                    - basis
+                        .iter()
+                        .zip(&tableau)
+                        .map(|(b, row)| cost(*b) * row[col])
+                        .sum::<f64>()
+            };
+            let entering_cols = if phase == 1 { n_cols } else { n_buttons };
+            let Some(col) = (0..entering_cols)
+                .filter(|col| !basis.contains(col))
+                .find(|col| reduced_cost(*col) < -EPSILON)
+            else {
+                break; // optimal
+            };
+            // ratio test, with ties broken by the smallest basic variable
+            let row = (0..n_rows)
+                .filter(|r| tableau[*r][col] > EPSILON)
+                .min_by(|a, b| {
+                    let ratio = |r: usize| tableau[r][n_cols] / tableau[r][col];
+                    ratio(*a)
+                        .total_cmp(&ratio(*b))
+                        .then(basis[*a].cmp(&basis[*b]))
+                })?; // unbounded, which can't happen with non-negative costs
+            pivot(&mut tableau, row, col);
+            basis[row] = col;
+        }
+        if phase == 1 {
+            let infeasibility: f64 = basis
+                .iter()
+                .zip(&tableau)
+                .filter(|(b, _)| **b >= n_buttons)
+                .map(|(_, row)| row[n_cols])
+                .sum();
+            if infeasibility > EPSILON {
+                return None;
+            }
+            // drive the remaining (zero) artificial variables out of the basis where possible
+            for row in 0..n_rows {
+                if basis[row] < n_buttons {
+                    continue;
+                }
+                if let Some(col) = (0..n_buttons)
+                    .filter(|col| !basis.contains(col))
+                    .find(|col| tableau[row][*col].abs() > EPSILON)
+                {
+                    pivot(&mut tableau, row, col);
+                    basis[row] = col;
+                }
+            }
+        }
+    }
+    Some(
+        basis
+            .iter()
+            .zip(&tableau)
+            .filter(|(b, _)| **b < n_buttons)
+            .map(|(_, row)| row[n_cols])
+            .sum(),
+    )
+}
+
+/// Pivot the simplex tableau on the given row and column
+fn pivot(tableau: &mut [Vec<f64>], row: usize, col: usize) {
+    let pivot_row: Vec<f64> = tableau[row].iter().map(|v| v / tableau[row][col]).collect();
+    for (r, current) in tableau.iter_mut().enumerate() {
+        if r == row {
+            current.clone_from(&pivot_row);
+            continue;
+        }
+        let factor = current[col];
+        if factor != 0. {
+            for (v, p) in current.iter_mut().zip(&pivot_row) {
+                *v -= factor * p;
+            }
+        }
+    }
+}
+
+/// Branch and bound search over the values of the free variables
+struct FreeSearch<'a> {
+    /// Maximum presses for each free variable
+    free_bounds: Vec<i128>,
+    /// The joltages incremented by each free variable's button
+    free_joltages: Vec<Vec<usize>>,
+    /// The joltages incremented by each pivot variable's button
+    pivot_joltages: Vec<Vec<usize>>,
+    /// Maximum presses for the pivot variable of each equation
+    pivot_bounds: Vec<i128>,
+    equations: &'a [PivotEquation],
+    /// Coefficient of each free variable in the objective
+    weights: Vec<f64>,
+    /// Current values of the free variables
+    values: Vec<i128>,
+    /// What remains of each joltage once the current free variables are pressed
+    remaining: Vec<i128>,
+    /// Best total presses found so far, and the corresponding free variable values
+    best: Option<(i128, Vec<i128>)>,
+}
+
+impl FreeSearch<'_> {
+    /// Lower bound of the total presses for the values of the free variables before `depth`, or `None` if there is no
+    /// solution
+    ///
+    /// These presses are added to the presses still needed for the remaining joltages when they can be fractional
+    /// (see [`relaxed_min_presses`]).
+    fn lower_bound(&self, depth: usize) -> Option<i128> {
+        let pressed: i128 = self.values[..depth].iter().sum();
+        // the buttons which aren't pressed yet: the pivot variables and the next free variables
+        let buttons: Vec<&[usize]> = self
+            .pivot_joltages
+            .iter()
+            .chain(&self.free_joltages[depth..])
+            .map(Vec::as_slice)
+            .collect();
+        let relaxed = relaxed_min_presses(&buttons, &self.remaining)?;
+        // allow for rounding errors before rounding up to the next integer
+        let remaining = (relaxed - 1e-6).ceil() as i128;
+        Some(pressed + remaining)
+    }
+
+    /// Press the button of the free variable at `depth` `times` more (or fewer if negative)
+    fn press(&mut self, depth: usize, times: i128) {
+        self.values[depth] += times;
+        for i in &self.free_joltages[depth] {
+            self.remaining[*i] -= times;
+        }
+    }
+
+    fn run(&mut self, depth: usize) {
+        let Some(lower_bound) = self.lower_bound(depth) else {
+            return; // no solution, even with fractional presses
+        };
+        if self
+            .best
+            .as_ref()
+            .is_some_and(|(best, _)| lower_bound >= *best)
+        {
+            return; // can't improve
+        }
+        if depth == self.values.len() {
+            let mut total: i128 = self.values.iter().sum();
+            for (eq, bound) in self.equations.iter().zip(&self.pivot_bounds) {
+                match eq.solve(&self.values) {
+                    Some(v) if (0..=*bound).contains(&v) => total += v,
+                    _ => return,
+                }
+            }
+            if self.best.as_ref().is_none_or(|(best, _)| total < *best) {
+                self.best = Some((total, self.values.clone()));
+            }
+            return;
+        }
+        // narrow down the range of values for this variable so that all pivot variables can stay within bounds
+        let (mut lo, mut hi) = (0, self.free_bounds[depth]);
+        for (eq, bound) in self.equations.iter().zip(&self.pivot_bounds) {
+            let (eq_lo, eq_hi) = eq.free_range(&self.values, depth, &self.free_bounds, *bound);
+            lo = lo.max(eq_lo);
+            hi = hi.min(eq_hi);
+        }
+        if lo > hi {
+            return;
         }
-        *button >>= 1;
-        b += 1;
+        // try the values lowering the objective first, to find good solutions early
+        let (first, step) = if self.weights[depth] >= 0. {
+            (lo, 1)
+        } else {
+            (hi, -1)
+        };
+        self.press(depth, first);
+        for i in 0..=hi - lo {
+            if i > 0 {
+                self.press(depth, step);
+            }
+            // skip the values where a joltage goes over its target
+            if self.remaining.iter().all(|r| *r >= 0) {
+                self.run(depth + 1);
+            }
+        }
+        let value = self.values[depth];
+        self.press(depth, -value);
     }
-    min
+}
+
+/// Find the number of presses for each button with integer linear programming
+fn solve_joltages_lp(machine: &Machine) -> Option<Vec<u64>> {
+    // the problem to solve is a set of linear equations
+    let mut problem = variables!();
+    // the variables represent how many times we have to press each button
+    let vars = machine.buttons.iter().map(|b| {
+        variable()
+            .integer() // we're only interested in integer solutions
+            .min(0) // values can't be negative
+            .max(max_button_presses(b, &machine.joltages)) // presses are bounded by the desired joltage
+    });
+    let vars: Vec<Variable> = problem.add_all(vars);
+    // the objective to minimize is the sum of all button presses
+    let objective: Expression = vars.iter().sum();
+    let mut model = problem.minimise(objective).using(default_solver);
+    // add constraints
+    for (i, jolt) in machine.joltages.iter().copied().enumerate() {
+        // for each joltage, first retrieve which buttons can affect it
+        let buttons_idx = buttons_idx_for_joltage(i, &machine.buttons);
+        // construct an expression which is the sum of all button presses for the buttons that can affect
+        // this joltage
+        let sum: Expression = buttons_idx.into_iter().map(|i| vars[i]).sum();
+        // this is the target joltage
+        let jolt = Expression::from_other_affine(jolt);
+        // add a constraint that all button presses should equal to the joltage value
+        model = model.with(constraint!(jolt == sum));
+    }
+    let solution = model.solve().ok()?; // magic 🪄
+    Some(
+        vars.into_iter()
+            .map(|v| solution.value(v).round().max(0.) as u64)
+            .collect(),
+    )
+}
+
+/// Find which buttons to press (by index) to reach the target lights state, with as few presses as possible
+fn lights_buttons(machine: &Machine) -> std::result::Result<Vec<usize>, SolveError> {
+    Ok(solve_lights(&machine.buttons, &machine.target)?
+        .ones()
+        .collect())
+}
+
+/// Check that pressing the given buttons (by index) from the initial state gives the target lights state
+fn verify_lights(machine: &Machine, pressed: &[usize]) -> bool {
+    pressed.iter().fold(Lights::default(), |lights, b| {
+        lights.press_button(&machine.buttons[*b])
+    }) == machine.target
+}
+
+/// Check that pressing each button the given number of times gives exactly the required joltages
+fn verify_joltages(machine: &Machine, presses: &[u64]) -> bool {
+    let mut counters = vec![0; machine.joltages.len()];
+    for (button, n) in machine.buttons.iter().zip(presses) {
+        for (i, counter) in counters.iter_mut().enumerate() {
+            if button.contains(i) {
+                *counter += n;
+            }
+        }
+    }
+    counters
+        .into_iter()
+        .zip(&machine.joltages)
+        .all(|(counter, jolt)| counter == u64::from(*jolt))
+}
+
+/// Describe the solution of both parts for a machine
+fn explain_machine(machine: &Machine, solver: JoltageSolver) -> String {
+    let status = |ok: bool| if ok { "ok" } else { "FAILED" };
+    let lights: String = (0..machine.joltages.len())
+        .map(|i| if machine.target.contains(i) { '#' } else { '.' })
+        .collect();
+    let lights = match lights_buttons(machine) {
+        Ok(pressed) => format!(
+            "[{lights}] <- {} ({} presses, {})",
+            pressed
+                .iter()
+                .map(|b| machine.buttons[*b].to_string())
+                .join(" "),
+            pressed.len(),
+            status(verify_lights(machine, &pressed))
+        ),
+        Err(SolveError::Unreachable) => format!("[{lights}] <- unreachable"),
+        Err(SolveError::TooLarge) => format!("[{lights}] <- too many button combinations"),
+    };
+    let joltages = format!("{{{}}}", machine.joltages.iter().join(","));
+    let joltages = match solver.solve(machine) {
+        Ok(presses) => format!(
+            "{joltages} <- {} ({} presses, {})",
+            machine
+                .buttons
+                .iter()
+                .zip(&presses)
+                .filter(|(_, n)| **n > 0)
+                .map(|(b, n)| format!("{b}x{n}"))
+                .join(" "),
+            presses.iter().sum::<u64>(),
+            status(verify_joltages(machine, &presses))
+        ),
+        Err(SolveError::Unreachable) => format!("{joltages} <- unreachable"),
+        Err(SolveError::TooLarge) => format!("{joltages} <- too large for the exact solver"),
+    };
+    format!("  {lights}\n  {joltages}")
 }
 
 pub struct Day10;
 
-fn parse_lights(input: &mut &str) -> Result<Lights> {
+/// Parse the target lights state, returning it along with the number of lights
+fn parse_lights(input: &mut &str) -> Result<(Lights, usize)> {
     let lights: Vec<_> = delimited(
         '[',
         repeat(1.., one_of(('.', '#')).map(|c: char| c == '#')),
         ']',
     )
     .parse_next(input)?;
-    Ok(lights.as_slice().into())
+    Ok((lights.as_slice().into(), lights.len()))
 }
 
-fn parse_button(input: &mut &str) -> Result<Button> {
-    let indices: Vec<_> =
-        delimited('(', separated(1.., dec_uint::<_, u8, _>, ','), ')').parse_next(input)?;
-    Ok(indices.as_slice().into())
+/// Parse a button, rejecting indices which don't refer to one of the `lights`
+fn parse_button(lights: usize) -> impl FnMut(&mut &str) -> Result<Button> {
+    move |input| {
+        let indices: Vec<_> = delimited(
+            '(',
+            separated(1.., dec_uint::<_, usize, _>.verify(|i| *i < lights), ','),
+            ')',
+        )
+        .parse_next(input)?;
+        Ok(indices.as_slice().into())
+    }
 }
 
-fn parse_buttons(input: &mut &str) -> Result<Vec<Button>> {
-    separated(1.., parse_button, ' ').parse_next(input)
+/// Parse the buttons of a machine with the given number of lights
+fn parse_buttons(lights: usize) -> impl FnMut(&mut &str) -> Result<Vec<Button>> {
+    move |input| separated(1.., parse_button(lights), ' ').parse_next(input)
 }
 
 fn parse_joltages(input: &mut &str) -> Result<Vec<u16>> {
@@ -200,98 +887,99 @@ fn parse_joltages(input: &mut &str) -> Result<Vec<u16>> {
 }
 
 fn parse_machine(input: &mut &str) -> Result<Machine> {
-    seq! {Machine {
-        target: parse_lights,
+    let (target, lights) = parse_lights.parse_next(input)?;
+    let (buttons, joltages) = seq!(
         _: ' ',
-        buttons: parse_buttons,
+        parse_buttons(lights),
         _: ' ',
-        joltages: parse_joltages
-    }}
-    .parse_next(input)
+        // one joltage counter per light
+        parse_joltages.verify(|joltages: &Vec<u16>| joltages.len() == lights),
+    )
+    .parse_next(input)?;
+    Ok(Machine {
+        target,
+        buttons,
+        joltages,
+    })
 }
 
 impl Day for Day10 {
-    type Input = Vec<Machine>;
+    type Input<'a> = Vec<Machine>;
 
-    fn parser(input: &mut &str) -> Result<Self::Input> {
-        separated(1.., parse_machine, newline).parse_next(input)
+    fn parser<'a>(input: &mut &'a str) -> Result<Self::Input<'a>> {
+        // not `separated`, which would backtrack on an invalid machine and lose the reason why it's invalid
+        let mut machines = vec![parse_machine.parse_next(input)?];
+        while opt(newline).parse_next(input)?.is_some() {
+            machines.push(parse_machine.parse_next(input)?);
+        }
+        Ok(machines)
     }
 
-    type Output1 = usize;
+    type Output1 = Presses;
 
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        input
-            .iter()
-            .map(|machine| a_star(&machine.buttons, Lights::default(), machine.target).unwrap())
-            .sum()
-    }
-
-    type Output2 = usize;
-
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        let mut res = 0;
-        for machine in input {
-            // the problem to solve is a set of linear equations
-            let mut problem = variables!();
-            // the variables represent how many times we have to press each button
-            let vars = machine.buttons.iter().map(|b| {
-                variable()
-                    .integer() // we're only interested in integer solutions
-                    .min(0) // values can't be negative
-                    .max(max_button_presses(*b, &machine.joltages)) // presses are bounded by the desired joltage
-            });
-            let vars: Vec<Variable> = problem.add_all(vars);
-            // the objective to minimize is the sum of all button presses
-            let objective: Expression = vars.iter().sum();
-            let mut model = problem.minimise(objective).using(default_solver);
-            // add constraints
-            for (i, jolt) in machine.joltages.iter().copied().enumerate() {
-                // for each joltage, first retrieve which buttons can affect it
-                let buttons_idx = buttons_idx_for_joltage(i, &machine.buttons);
-                // construct an expression which is the sum of all button presses for the buttons that can affect
-                // this joltage
-                let sum: Expression = buttons_idx.into_iter().map(|i| vars[i]).sum();
-                // this is the target joltage
-                let jolt = Expression::from_other_affine(jolt);
-                // add a constraint that all button presses should equal to the joltage value
-                model = model.with(constraint!(jolt == sum));
+    fn part_1(input: &Self::Input<'_>) -> Self::Output1 {
+        let mut total = 0;
+        for (i, machine) in input.iter().enumerate() {
+            let pressed = match lights_buttons(machine) {
+                Ok(pressed) => pressed,
+                Err(SolveError::Unreachable) => return Presses::Unreachable(i),
+                Err(SolveError::TooLarge) => return Presses::TooLarge(i),
+            };
+            if !verify_lights(machine, &pressed) {
+                return Presses::Invalid(i);
             }
-            let solution = model.solve().unwrap(); // magic 🪄
-            // the sum of all variables is the total number of button presses, let's accumulate them
-            res += vars
-                .into_iter()
-                .map(|v| solution.value(v).round() as usize)
-                .sum::<usize>();
+            total += pressed.len();
         }
-        res
+        Presses::Total(total)
     }
-}
 
-impl Deref for Lights {
-    type Target = u16;
+    type Output2 = Presses;
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+    fn part_2(input: &Self::Input<'_>) -> Self::Output2 {
+        let solver = JoltageSolver::from_env();
+        let mut total = 0;
+        for (i, machine) in input.iter().enumerate() {
+            let presses = match solver.solve(machine) {
+                Ok(presses) => presses,
+                Err(SolveError::Unreachable) => return Presses::Unreachable(i),
+                Err(SolveError::TooLarge) => return Presses::TooLarge(i),
+            };
+            // the LP solver works with floats, make sure the rounded values are actually a solution
+            if !verify_joltages(machine, &presses) {
+                return Presses::Invalid(i);
+            }
+            total += presses.iter().sum::<u64>() as usize;
+        }
+        Presses::Total(total)
     }
-}
 
-impl DerefMut for Lights {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+    fn explain(input: &Self::Input<'_>) -> Option<String> {
+        let solver = JoltageSolver::from_env();
+        Some(
+            input
+                .iter()
+                .enumerate()
+                .map(|(i, machine)| {
+                    format!("Machine {}:\n{}", i + 1, explain_machine(machine, solver))
+                })
+                .join("\n"),
+        )
     }
 }
 
-impl Deref for Button {
-    type Target = u16;
+impl Deref for Lights {
+    type Target = Bits;
 
     fn deref(&self) -> &Self::Target {
         &self.0
     }
 }
 
-impl DerefMut for Button {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+impl Deref for Button {
+    type Target = Bits;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
     }
 }
 
@@ -307,12 +995,121 @@ mod tests {
     #[test]
     fn test_part1() {
         let parsed = Day10::parser(&mut INPUT).unwrap();
-        assert_eq!(Day10::part_1(&parsed), 7);
+        assert_eq!(Day10::part_1(&parsed), Presses::Total(7));
+    }
+
+    #[test]
+    fn test_solve_lights_brute_force() {
+        let parsed = Day10::parser(&mut INPUT).unwrap();
+        for machine in parsed {
+            // try all subsets of buttons
+            let expected = (0u64..1 << machine.buttons.len())
+                .filter(|subset| {
+                    let lights = machine
+                        .buttons
+                        .iter()
+                        .enumerate()
+                        .filter(|(b, _)| (subset >> b) & 1 == 1)
+                        .fold(Lights::default(), |l, (_, button)| l.press_button(button));
+                    lights == machine.target
+                })
+                .map(u64::count_ones)
+                .min();
+            let res = solve_lights(&machine.buttons, &machine.target).map(|b| b.count_ones());
+            assert_eq!(res.ok(), expected);
+        }
+    }
+
+    #[test]
+    fn test_explain() {
+        let parsed = Day10::parser(&mut INPUT).unwrap();
+        let explanation = explain_machine(&parsed[0], JoltageSolver::Exact);
+        let lines: Vec<_> = explanation.lines().collect();
+        assert_eq!(lines[0], "  [.##.] <- (1,3) (2,3) (2 presses, ok)");
+        assert!(lines[1].starts_with("  {3,5,4,7} <- "));
+        assert!(lines[1].ends_with("(10 presses, ok)"));
+        assert!(!verify_lights(&parsed[0], &[0]));
+        assert!(!verify_joltages(&parsed[0], &[1, 0, 0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn test_part1_unreachable() {
+        let parsed = Day10::parser(&mut "[.##.] (3) (1,3) {3,5,4,7}").unwrap();
+        assert_eq!(Day10::part_1(&parsed), Presses::Unreachable(0));
+    }
+
+    #[test]
+    fn test_wide_machine() {
+        // 20 lights, more than fit in a u16
+        let line = "[.................###] (17,18) (0,19) (0) (1) {0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,1,1,0}";
+        let parsed = Day10::parser(&mut &*line).unwrap();
+        assert_eq!(parsed[0].buttons[1].to_string(), "(0,19)");
+        assert_eq!(lights_buttons(&parsed[0]), Ok(vec![0, 1, 2]));
+        assert_eq!(Day10::part_2(&parsed), Presses::Total(1));
+        // button index out of range for the 4 lights
+        assert!(Day10::parser(&mut "[.##.] (3) (1,4) {3,5,4,7}").is_err());
+        assert!(Day10::parser(&mut "[.##.] (3) (1,3) {3,5,4}").is_err());
     }
 
     #[test]
     fn test_part2() {
         let parsed = Day10::parser(&mut INPUT).unwrap();
-        assert_eq!(Day10::part_2(&parsed), 33);
+        assert_eq!(Day10::part_2(&parsed), Presses::Total(33));
+    }
+
+    #[test]
+    fn test_joltage_solvers() {
+        let parsed = Day10::parser(&mut INPUT).unwrap();
+        for machine in parsed {
+            let exact = JoltageSolver::Exact.solve(&machine).unwrap();
+            let lp = JoltageSolver::Lp.solve(&machine).unwrap();
+            assert!(verify_joltages(&machine, &exact));
+            assert!(verify_joltages(&machine, &lp));
+            assert_eq!(exact.iter().sum::<u64>(), lp.iter().sum::<u64>());
+        }
+    }
+
+    #[test]
+    fn test_many_free_variables() {
+        // 13 buttons for 6 lights, so at least 7 free variables, and large joltages
+        let line = "[.#.#.#] (0,1,2,5) (0,1,2,4,5) (0,3,4,5) (4) (0,4) (2,3) (0,1,2,3,4) (0,1,2) (0) (0,1,3,4,5) (1,2) \
+            (1,2,3,4,5) (2,3,4,5) {142,123,179,68,94,99}";
+        let parsed = Day10::parser(&mut &*line).unwrap();
+        let exact = JoltageSolver::Exact.solve(&parsed[0]).unwrap();
+        let lp = JoltageSolver::Lp.solve(&parsed[0]).unwrap();
+        assert!(verify_joltages(&parsed[0], &exact));
+        assert_eq!(exact.iter().sum::<u64>(), 198);
+        assert_eq!(lp.iter().sum::<u64>(), 198);
+    }
+
+    #[test]
+    fn test_limits() {
+        // 300 lights and 300 buttons: light 299 is toggled by the last button only
+        let lights = format!("[{}#]", ".".repeat(299));
+        let buttons = (0..300).map(|i| format!("({i})")).join(" ");
+        let line = format!("{lights} {buttons} {{{}1}}", "0,".repeat(299));
+        let parsed = Day10::parser(&mut line.as_str()).unwrap();
+        assert_eq!(lights_buttons(&parsed[0]), Ok(vec![299]));
+        assert_eq!(Day10::part_2(&parsed), Presses::Total(1));
+        // 64 buttons toggling the same light leave 63 free buttons, far too many combinations to enumerate
+        let line = format!("[.#] {} {{1,1}}", ["(1)"; 64].join(" "));
+        let parsed = Day10::parser(&mut line.as_str()).unwrap();
+        assert_eq!(Day10::part_1(&parsed), Presses::TooLarge(0));
+        assert_eq!(
+            Presses::TooLarge(0).to_string(),
+            "too large to solve (machine 1)"
+        );
+    }
+
+    #[test]
+    fn test_relaxed_min_presses() {
+        // (1,2) can't be pressed, so (0,1) is pressed twice
+        let buttons: [&[usize]; 3] = [&[0, 1], &[0], &[1, 2]];
+        assert_eq!(relaxed_min_presses(&buttons, &[2, 2, 0]), Some(2.));
+        // each button pressed half a time, although there is no integer solution
+        let presses = relaxed_min_presses(&[&[0, 1], &[1, 2], &[0, 2]], &[1, 1, 1]).unwrap();
+        assert!((presses - 1.5).abs() < 1e-9);
+        // the third joltage can't be reached
+        assert_eq!(relaxed_min_presses(&[&[0, 1]], &[1, 1, 1]), None);
     }
 }
@@ -1,193 +1,482 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::{Display, Write as _},
+};
 
-use itertools::Itertools;
-use pathfinding::prelude::count_paths;
 use petgraph::{
     Direction, Graph,
-    algo::{has_path_connecting, toposort},
+    algo::{tarjan_scc, toposort},
     graph::NodeIndex,
-    visit::EdgeRef as _,
+    visit::{Dfs, EdgeRef as _, IntoNeighbors as _, NodeFiltered, Reversed},
 };
 use winnow::{
-    Parser as _, Result,
-    ascii::{alpha1, newline},
-    combinator::separated,
+    Parser as _,
+    ascii::newline,
+    combinator::{preceded, repeat, separated},
+    token::take_while,
 };
 
 use crate::days::Day;
 
-#[derive(Debug, Clone)]
-pub struct Server {
-    nodes: HashMap<String, NodeIndex>,
-    graph: Graph<String, ()>,
+/// The devices graph, with node names borrowed from the input
+#[derive(Debug, Clone, Default)]
+pub struct Server<'a> {
+    nodes: HashMap<&'a str, NodeIndex>,
+    graph: Graph<&'a str, ()>,
 }
 
-fn get_bridge_nodes(g: &Graph<String, ()>) -> Vec<(usize, NodeIndex)> {
-    toposort(g, None)
-        .unwrap()
-        .into_iter()
-        .enumerate()
-        .filter(|(_, n)| g.neighbors_directed(*n, Direction::Incoming).count() > 6)
-        .collect()
+/// Why paths couldn't be counted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathsError {
+    UnknownDevice(String),
+    DuplicateWaypoint(String),
+    TooManyWaypoints(usize),
+    /// A cycle between the start and the end, so there are infinitely many paths (the first device is repeated at
+    /// the end)
+    Cycle(Vec<String>),
+    /// More paths than fit in 64 bits
+    Overflow,
 }
 
-fn get_bridge_layers(
-    bridge_nodes: Vec<(usize, NodeIndex)>,
-    start: NodeIndex,
-    end: NodeIndex,
-) -> Vec<Vec<NodeIndex>> {
-    let mut bridge_layers = Vec::<Vec<_>>::new();
-    bridge_layers.push(vec![start]);
-    for (_, chunk) in &bridge_nodes
-        .into_iter()
-        .tuple_windows()
-        .chunk_by(|((i, _), (j, _))| (*i).abs_diff(*j) <= 20)
-    {
-        let nodes = chunk.into_iter().map(|((_, n), _)| n).collect_vec();
-        if nodes.len() == 1 {
-            bridge_layers
-                .last_mut()
-                .unwrap()
-                .push(*nodes.first().unwrap());
-        } else {
-            bridge_layers.push(nodes);
+impl Display for PathsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PathsError::UnknownDevice(name) => write!(f, "unknown device {name}"),
+            PathsError::DuplicateWaypoint(name) => write!(f, "duplicate waypoint {name}"),
+            PathsError::TooManyWaypoints(n) => {
+                write!(f, "too many waypoints to visit in any order ({n})")
+            }
+            PathsError::Cycle(cycle) => {
+                write!(f, "infinitely many paths, cycle {}", cycle.join(" -> "))
+            }
+            PathsError::Overflow => write!(f, "too many paths to count in 64 bits"),
         }
     }
-    bridge_layers.push(vec![end]);
-    bridge_layers
 }
 
-fn count(
-    g: &Graph<String, ()>,
-    from: NodeIndex,
-    to: NodeIndex,
-    via: Option<NodeIndex>,
-    counts: &mut HashMap<NodeIndex, usize>,
-) {
-    let c = if let Some(via) = via {
-        let c1 = count_paths(
-            from,
-            |&n| g.neighbors_directed(n, Direction::Outgoing),
-            |&n| n == via,
-        );
-        let c2 = count_paths(
-            via,
-            |&n| g.neighbors_directed(n, Direction::Outgoing),
-            |&n| n == to,
-        );
-        // paths from `from` to `end` going through the via node
-        c1 * c2
-    } else {
-        count_paths(
-            from,
-            |&n| g.neighbors_directed(n, Direction::Outgoing),
-            |&n| n == to,
-        )
-    };
-    // total paths reaching the `to` node
-    let c = c * counts.get(&from).unwrap_or(&1);
-    counts
-        .entry(to)
-        .and_modify(|num| {
-            *num += c;
-        })
-        .or_insert(c);
-}
+impl std::error::Error for PathsError {}
 
-pub struct Day11;
+/// The answer for a part, which can't be computed on some inputs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Paths {
+    Count(u64),
+    Failed(PathsError),
+}
 
-fn parse_outputs<'a>(input: &mut &'a str) -> Result<Vec<&'a str>> {
-    separated(1.., alpha1, ' ').parse_next(input)
+impl From<Result<u64, PathsError>> for Paths {
+    fn from(value: Result<u64, PathsError>) -> Self {
+        value.map_or_else(Paths::Failed, Paths::Count)
+    }
 }
 
-fn parse_device<'a>(input: &mut &'a str) -> Result<(&'a str, Vec<&'a str>)> {
-    let (node, _, outputs) = (alpha1, ": ", parse_outputs).parse_next(input)?;
-    Ok((node, outputs))
+impl Display for Paths {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Paths::Count(n) => write!(f, "{n}"),
+            Paths::Failed(e) => write!(f, "{e}"),
+        }
+    }
 }
 
-impl Day for Day11 {
-    type Input = Server;
+impl<'a> Server<'a> {
+    /// Get a device, adding it to the graph if it wasn't seen yet
+    fn device(&mut self, name: &'a str) -> NodeIndex {
+        *self
+            .nodes
+            .entry(name)
+            .or_insert_with(|| self.graph.add_node(name))
+    }
 
-    fn parser(input: &mut &str) -> Result<Self::Input> {
-        let devices: Vec<_> = separated(1.., parse_device, newline).parse_next(input)?;
-        let mut graph = Graph::new();
-        let mut nodes = HashMap::new();
-        for (node, _) in &devices {
-            let idx = graph.add_node((*node).to_string());
-            nodes.insert((*node).to_string(), idx);
-        }
-        let out = graph.add_node("out".to_string());
-        nodes.insert("out".to_string(), out);
-        for (node, outputs) in &devices {
-            for output in outputs {
-                graph.add_edge(*nodes.get(*node).unwrap(), *nodes.get(*output).unwrap(), ());
-            }
+    /// Add a device and its connections to its outputs, which don't need to be declared beforehand
+    pub fn add_device(&mut self, name: &'a str, outputs: &[&'a str]) {
+        let node = self.device(name);
+        for output in outputs {
+            let output = self.device(output);
+            self.graph.add_edge(node, output, ());
         }
-        Ok(Server { nodes, graph })
     }
+}
 
-    type Output1 = usize;
-
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        let end = input.nodes.get("out").unwrap();
-        count_paths(
-            *input.nodes.get("you").unwrap(),
-            |&n| input.graph.neighbors_directed(n, Direction::Outgoing),
-            |n| n == end,
-        )
+impl Server<'_> {
+    /// Look up a device by name
+    fn node(&self, name: &str) -> Result<NodeIndex, PathsError> {
+        self.nodes
+            .get(name)
+            .copied()
+            .ok_or_else(|| PathsError::UnknownDevice(name.to_string()))
     }
 
-    type Output2 = usize;
-
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        let svr = *input.nodes.get("svr").unwrap();
-        let out = *input.nodes.get("out").unwrap();
-        let dac = *input.nodes.get("dac").unwrap();
-        let fft = *input.nodes.get("fft").unwrap();
-        let bridge_nodes = get_bridge_nodes(&input.graph);
-        let bridge_layers = get_bridge_layers(bridge_nodes, svr, out);
-        let mut num_paths = HashMap::<NodeIndex, usize>::new();
-        for (start_layer, end_layer) in bridge_layers.iter().tuple_windows() {
-            // disconnect output
-            let mut g = input.graph.clone();
-            for n in end_layer {
-                let edges = g
-                    .edges_directed(*n, Direction::Outgoing)
-                    .map(|e| e.id())
-                    .collect_vec();
-                for edge in edges {
-                    g.remove_edge(edge);
+    /// Find a cycle among the devices for which `keep` is true, if any
+    ///
+    /// The returned list of device names starts and ends with the same device.
+    fn find_cycle(&self, keep: impl Fn(NodeIndex) -> bool) -> Option<Vec<&str>> {
+        let graph = NodeFiltered::from_fn(&self.graph, keep);
+        // any strongly connected component with more than one node or with a self-loop contains a cycle
+        let component = tarjan_scc(&graph)
+            .into_iter()
+            .find(|c| c.len() > 1 || (&graph).neighbors(c[0]).any(|n| n == c[0]))?;
+        // BFS inside the component to find the shortest way back to its first node
+        let start = component[0];
+        let mut parents = HashMap::new();
+        let mut queue = VecDeque::from([start]);
+        while let Some(node) = queue.pop_front() {
+            for next in (&graph).neighbors(node) {
+                if !component.contains(&next) || parents.contains_key(&next) {
+                    continue;
                 }
-            }
-            if start_layer
-                .iter()
-                .any(|n| has_path_connecting(&g, *n, dac, None))
-            {
-                // special group with dac, only count paths going through dac
-                for start in start_layer {
-                    for end in end_layer {
-                        count(&input.graph, *start, *end, Some(dac), &mut num_paths);
+                parents.insert(next, node);
+                if next == start {
+                    let mut cycle = vec![self.graph[start]];
+                    let mut current = node;
+                    while current != start {
+                        cycle.push(self.graph[current]);
+                        current = parents[&current];
                     }
+                    cycle.push(self.graph[start]);
+                    cycle.reverse();
+                    return Some(cycle);
                 }
-                continue;
-            } else if start_layer
-                .iter()
-                .any(|n| has_path_connecting(&g, *n, fft, None))
-            {
-                // special group with fft, only count paths going through dac
-                for start in start_layer {
-                    for end in end_layer {
-                        count(&input.graph, *start, *end, Some(fft), &mut num_paths);
+                queue.push_back(next);
+            }
+        }
+        unreachable!("a strongly connected component always has a cycle through each node")
+    }
+
+    /// Count the paths from `from` to `to` which visit all the `via` waypoints
+    ///
+    /// The waypoints can be visited in any order, or in the given order if `ordered` is set. Only the devices which are
+    /// on some path from `from` to `to` matter, and they must not form a cycle (or there would be infinitely many
+    /// paths). We can then count paths with a DP over their topological order, where the state of a partial path is
+    /// the node it ends at plus which waypoints it visited so far (a bitmask, or how many of them when ordered).
+    pub fn count_paths(
+        &self,
+        from: &str,
+        to: &str,
+        via: &[&str],
+        ordered: bool,
+    ) -> Result<u64, PathsError> {
+        let from = self.node(from)?;
+        let to = self.node(to)?;
+        let mut waypoints = HashMap::new();
+        for (i, name) in via.iter().enumerate() {
+            if waypoints.insert(self.node(name)?, i).is_some() {
+                return Err(PathsError::DuplicateWaypoint((*name).to_string()));
+            }
+        }
+        if !ordered && via.len() > 16 {
+            return Err(PathsError::TooManyWaypoints(via.len()));
+        }
+        let (num_states, goal) = if ordered {
+            (via.len() + 1, via.len())
+        } else {
+            (1 << via.len(), (1 << via.len()) - 1)
+        };
+        // the state of a path after entering `node`, or `None` if it visits the waypoints out of order
+        let step = |state: usize, node: NodeIndex| match waypoints.get(&node) {
+            None => Some(state),
+            Some(i) if !ordered => Some(state | 1 << i),
+            Some(i) if *i == state => Some(state + 1),
+            Some(_) => None,
+        };
+        let order = self.order_between(from, to)?;
+        // number of paths from `from` to each node, for each state
+        let mut counts = vec![0u64; self.graph.node_count() * num_states];
+        if let Some(state) = step(0, from) {
+            counts[from.index() * num_states + state] = 1;
+        }
+        for node in order {
+            if node == to {
+                continue; // paths end here
+            }
+            for state in 0..num_states {
+                let count = counts[node.index() * num_states + state];
+                if count == 0 {
+                    continue;
+                }
+                for next in self.graph.neighbors_directed(node, Direction::Outgoing) {
+                    if let Some(next_state) = step(state, next) {
+                        let next_count = &mut counts[next.index() * num_states + next_state];
+                        *next_count = next_count.checked_add(count).ok_or(PathsError::Overflow)?;
                     }
                 }
-                continue;
             }
-            for start in start_layer {
-                for end in end_layer {
-                    count(&input.graph, *start, *end, None, &mut num_paths);
+        }
+        Ok(counts[to.index() * num_states + goal])
+    }
+
+    /// The devices which are on some path from `from` to `to`, in topological order
+    ///
+    /// Returns an error if they contain a cycle.
+    fn order_between(&self, from: NodeIndex, to: NodeIndex) -> Result<Vec<NodeIndex>, PathsError> {
+        let relevant = self.between(from, to);
+        toposort(
+            &NodeFiltered::from_fn(&self.graph, |n| relevant[n.index()]),
+            None,
+        )
+        .map_err(|_| {
+            let cycle = self.find_cycle(|n| relevant[n.index()]).unwrap_or_default();
+            PathsError::Cycle(cycle.into_iter().map(str::to_string).collect())
+        })
+    }
+
+    /// Export the graph in Graphviz DOT format, with the number of paths from `from` to `to` through each device
+    ///
+    /// The `waypoints` are highlighted, and the devices which are not on any path from `from` to `to` (unreachable or
+    /// dead ends) are faded. Devices with more paths than fit in 64 bits are labeled with "overflow" instead.
+    pub fn to_dot(&self, from: &str, to: &str, waypoints: &[&str]) -> Result<String, PathsError> {
+        let from = self.node(from)?;
+        let to = self.node(to)?;
+        let waypoints = waypoints
+            .iter()
+            .map(|name| self.node(name))
+            .collect::<Result<Vec<_>, _>>()?;
+        let order = self.order_between(from, to)?;
+        let mut relevant = vec![false; self.graph.node_count()];
+        for node in &order {
+            relevant[node.index()] = true;
+        }
+        // number of paths from `from` to each device, and from each device to `to` (`None` if they overflow)
+        let add = |a: Option<u64>, b: Option<u64>| a?.checked_add(b?);
+        let mut paths_from = vec![Some(0u64); self.graph.node_count()];
+        let mut paths_to = vec![Some(0u64); self.graph.node_count()];
+        paths_from[from.index()] = Some(u64::from(relevant[from.index()]));
+        paths_to[to.index()] = Some(u64::from(relevant[to.index()]));
+        for node in &order {
+            for next in self.graph.neighbors_directed(*node, Direction::Outgoing) {
+                if relevant[next.index()] {
+                    paths_from[next.index()] =
+                        add(paths_from[next.index()], paths_from[node.index()]);
+                }
+            }
+        }
+        for node in order.iter().rev() {
+            for next in self.graph.neighbors_directed(*node, Direction::Outgoing) {
+                if relevant[next.index()] {
+                    paths_to[node.index()] = add(paths_to[node.index()], paths_to[next.index()]);
                 }
             }
         }
-        *num_paths.get(&out).unwrap()
+
+        let mut dot = String::from("digraph server {\n    node [shape=box];\n");
+        for node in self.graph.node_indices() {
+            let name = self.graph[node];
+            let i = node.index();
+            let style = if waypoints.contains(&node) {
+                ", style=filled, fillcolor=gold"
+            } else if node == from || node == to {
+                ", style=bold"
+            } else {
+                ""
+            };
+            let attributes = if relevant[i] {
+                let paths = paths_from[i]
+                    .zip(paths_to[i])
+                    .and_then(|(before, after)| before.checked_mul(after))
+                    .map_or_else(|| "overflow".to_string(), |n| n.to_string());
+                format!("label=\"{name}\\n{paths}\"{style}")
+            } else {
+                format!("label=\"{name}\"{style}, color=gray, fontcolor=gray")
+            };
+            writeln!(dot, "    \"{name}\" [{attributes}];").unwrap();
+        }
+        for edge in self.graph.edge_references() {
+            let (source, target) = (edge.source(), edge.target());
+            let faded = if relevant[source.index()] && relevant[target.index()] {
+                ""
+            } else {
+                " [color=gray]"
+            };
+            writeln!(
+                dot,
+                "    \"{}\" -> \"{}\"{faded};",
+                self.graph[source], self.graph[target]
+            )
+            .unwrap();
+        }
+        dot.push_str("}\n");
+        Ok(dot)
+    }
+
+    /// Which devices (by index) are reachable from `from` and can reach `to`
+    fn between(&self, from: NodeIndex, to: NodeIndex) -> Vec<bool> {
+        let mut relevant = vec![false; self.graph.node_count()];
+        let mut dfs = Dfs::new(&self.graph, from);
+        while let Some(node) = dfs.next(&self.graph) {
+            relevant[node.index()] = true;
+        }
+        let mut reachable = vec![false; self.graph.node_count()];
+        let reversed = Reversed(&self.graph);
+        let mut dfs = Dfs::new(reversed, to);
+        while let Some(node) = dfs.next(reversed) {
+            reachable[node.index()] = true;
+        }
+        for (r, reaches_end) in relevant.iter_mut().zip(reachable) {
+            *r &= reaches_end;
+        }
+        relevant
+    }
+}
+
+pub struct Day11;
+
+/// Parse a device name, made of ASCII letters, digits and underscores
+fn parse_name<'a>(input: &mut &'a str) -> winnow::Result<&'a str> {
+    take_while(1.., |c: char| c.is_ascii_alphanumeric() || c == '_').parse_next(input)
+}
+
+fn parse_device<'a>(input: &mut &'a str) -> winnow::Result<(&'a str, Vec<&'a str>)> {
+    let (node, _, outputs) =
+        (parse_name, ':', repeat(0.., preceded(' ', parse_name))).parse_next(input)?;
+    Ok((node, outputs))
+}
+
+impl Day for Day11 {
+    type Input<'a> = Server<'a>;
+
+    fn parser<'a>(input: &mut &'a str) -> winnow::Result<Self::Input<'a>> {
+        let devices: Vec<_> = separated(1.., parse_device, newline).parse_next(input)?;
+        let mut server = Server::default();
+        for (node, outputs) in &devices {
+            server.add_device(node, outputs);
+        }
+        Ok(server)
+    }
+
+    type Output1 = Paths;
+
+    fn part_1(input: &Self::Input<'_>) -> Self::Output1 {
+        input.count_paths("you", "out", &[], false).into()
+    }
+
+    type Output2 = Paths;
+
+    fn part_2(input: &Self::Input<'_>) -> Self::Output2 {
+        input
+            .count_paths("svr", "out", &["dac", "fft"], false)
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT_1: &str = "aaa: you hhh
+you: bbb ccc
+bbb: ddd eee
+ccc: ddd eee fff
+ddd: ggg
+eee: out
+fff: out
+ggg: out
+hhh: ccc fff iii
+iii: out";
+
+    const INPUT_2: &str = "svr: aaa bbb
+aaa: fft
+fft: ccc
+bbb: tty
+tty: ccc
+ccc: ddd eee
+ddd: hub
+hub: fff
+eee: dac
+dac: fff
+fff: ggg hhh
+ggg: out
+hhh: out";
+
+    #[test]
+    fn test_part1() {
+        let parsed = Day11::parse(INPUT_1).unwrap();
+        assert_eq!(Day11::part_1(&parsed), Paths::Count(5));
+    }
+
+    #[test]
+    fn test_part2() {
+        let parsed = Day11::parse(INPUT_2).unwrap();
+        assert_eq!(Day11::part_2(&parsed), Paths::Count(2));
+        assert_eq!(parsed.count_paths("svr", "out", &[], false).unwrap(), 8);
+        assert_eq!(
+            parsed
+                .count_paths("svr", "out", &["fft", "dac"], true)
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            parsed
+                .count_paths("svr", "out", &["dac", "fft"], true)
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            parsed.count_paths("svr", "fff", &["ccc"], false).unwrap(),
+            4
+        );
+        assert!(parsed.count_paths("svr", "nope", &[], false).is_err());
+    }
+
+    #[test]
+    fn test_cycles_and_names() {
+        // `out` and `sink_2` are only referenced, the cycle between `b_2` and `c` can't reach `out`
+        let parsed = Day11::parse("a1: b_2 out\nb_2: c\nc: b_2\nx: y\ny: x sink_2").unwrap();
+        assert_eq!(parsed.count_paths("a1", "out", &[], false), Ok(1));
+        let Err(PathsError::Cycle(cycle)) = parsed.count_paths("a1", "c", &[], false) else {
+            panic!("expected a cycle");
+        };
+        assert_eq!(cycle.len(), 3);
+        assert_eq!(cycle[0], cycle[2]);
+        assert!(cycle.contains(&"b_2".to_string()) && cycle.contains(&"c".to_string()));
+        assert!(matches!(
+            parsed.count_paths("x", "sink_2", &[], false),
+            Err(PathsError::Cycle(_))
+        ));
+        assert!(parsed.find_cycle(|_| true).is_some());
+        assert_eq!(
+            Day11::part_1(&parsed),
+            Paths::Failed(PathsError::UnknownDevice("you".into()))
+        );
+    }
+
+    #[test]
+    fn test_dot() {
+        let parsed = Day11::parse(INPUT_2).unwrap();
+        let dot = parsed.to_dot("bbb", "out", &["ccc"]).unwrap();
+        assert!(dot.starts_with("digraph server {\n"));
+        // 4 paths from bbb to out, all through tty and ccc
+        assert!(dot.contains("    \"bbb\" [label=\"bbb\\n4\", style=bold];\n"));
+        assert!(dot.contains("    \"tty\" [label=\"tty\\n4\"];\n"));
+        assert!(dot.contains("    \"ccc\" [label=\"ccc\\n4\", style=filled, fillcolor=gold];\n"));
+        assert!(dot.contains("    \"ddd\" [label=\"ddd\\n2\"];\n"));
+        // svr is unreachable from bbb
+        assert!(dot.contains("    \"svr\" [label=\"svr\", color=gray, fontcolor=gray];\n"));
+        assert!(dot.contains("    \"svr\" -> \"bbb\" [color=gray];\n"));
+        assert!(dot.contains("    \"bbb\" -> \"tty\";\n"));
+    }
+
+    /// A chain of `n` diamonds from `n0` to `n{n}`, with 2^n paths
+    fn diamonds(n: usize) -> String {
+        (0..n)
+            .map(|i| format!("n{i}: a{i} b{i}\na{i}: n{j}\nb{i}: n{j}", j = i + 1))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_overflow() {
+        let input = diamonds(63);
+        let parsed = Day11::parse(&input).unwrap();
+        assert_eq!(parsed.count_paths("n0", "n63", &[], false), Ok(1 << 63));
+        let input = diamonds(64);
+        let parsed = Day11::parse(&input).unwrap();
+        assert_eq!(
+            parsed.count_paths("n0", "n64", &[], false),
+            Err(PathsError::Overflow)
+        );
+        // 2^63 paths through a31, but 2^64 through n0, n32 and n64
+        let dot = parsed.to_dot("n0", "n64", &[]).unwrap();
+        assert!(dot.contains("    \"a31\" [label=\"a31\\n9223372036854775808\"];\n"));
+        assert!(dot.contains("    \"n32\" [label=\"n32\\noverflow\"];\n"));
+        assert!(dot.contains("    \"n0\" [label=\"n0\\noverflow\", style=bold];\n"));
     }
 }
@@ -1,15 +1,18 @@
+use std::{
+    collections::HashSet,
+    fmt::{Display, Write as _},
+};
+
 use itertools::{Itertools, repeat_n};
 use winnow::{
     Parser as _, Result,
     ascii::{dec_uint, newline},
-    combinator::{delimited, repeat, separated, seq},
+    combinator::{delimited, preceded, repeat, separated},
     token::one_of,
 };
 
 use crate::days::Day;
 
-const SHAPE_SIZE: usize = 3;
-
 /// A shape rotation
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum Rotation {
@@ -18,71 +21,49 @@ pub enum Rotation {
     ThreeQuarter,
 }
 
-/// A shape to place in the region
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-pub struct Shape([[bool; SHAPE_SIZE]; SHAPE_SIZE]);
+/// A shape to place in the region, which can be any polyomino
+///
+/// The tiles are normalized: the bounding box starts at (0, 0) and the list is sorted, so that two shapes with the
+/// same tiles are equal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Shape {
+    width: usize,
+    height: usize,
+    /// (x, y) coordinates of the tiles, sorted by row then column
+    tiles: Vec<(usize, usize)>,
+}
 
 impl Shape {
-    /// Get the area of a shape (how many tiles are occupied by it)
-    fn area(&self) -> usize {
-        self.0.iter().flatten().filter(|k| **k).count()
-    }
-
-    /// Check whether we can place this shape at the (x, y) coordinate in the grid
-    fn can_place(&self, grid: &[Vec<bool>], x: usize, y: usize) -> bool {
-        for dy in 0..SHAPE_SIZE {
-            for dx in 0..SHAPE_SIZE {
-                if !self.0[dy][dx] {
-                    continue;
-                }
-                let grid_x = x + dx;
-                let grid_y = y + dy;
-                if grid_y >= grid.len() || grid_x >= grid[0].len() {
-                    return false;
-                }
-                if grid[grid_y][grid_x] {
-                    return false;
-                }
-            }
-        }
-        true
-    }
-
-    /// Place this shape on the grid at (x, y)
-    fn place(self, grid: &mut [Vec<bool>], x: usize, y: usize) {
-        for dy in 0..SHAPE_SIZE {
-            for dx in 0..SHAPE_SIZE {
-                if self.0[dy][dx] {
-                    grid[y + dy][x + dx] = true;
-                }
-            }
+    /// Create a shape from its tiles, moving it to the top-left corner
+    fn new(tiles: impl IntoIterator<Item = (usize, usize)>) -> Self {
+        let tiles: Vec<_> = tiles.into_iter().collect();
+        let left = tiles.iter().map(|(x, _)| *x).min().unwrap_or(0);
+        let top = tiles.iter().map(|(_, y)| *y).min().unwrap_or(0);
+        let mut tiles: Vec<_> = tiles
+            .into_iter()
+            .map(|(x, y)| (x - left, y - top))
+            .collect();
+        tiles.sort_unstable_by_key(|(x, y)| (*y, *x));
+        tiles.dedup();
+        Self {
+            width: tiles.iter().map(|(x, _)| x + 1).max().unwrap_or(0),
+            height: tiles.iter().map(|(_, y)| y + 1).max().unwrap_or(0),
+            tiles,
         }
     }
 
-    /// Remove this shape from (x, y)
-    fn remove(self, grid: &mut [Vec<bool>], x: usize, y: usize) {
-        for dy in 0..SHAPE_SIZE {
-            for dx in 0..SHAPE_SIZE {
-                if self.0[dy][dx] {
-                    grid[y + dy][x + dx] = false;
-                }
-            }
-        }
+    /// Get the area of a shape (how many tiles are occupied by it)
+    fn area(&self) -> usize {
+        self.tiles.len()
     }
 
     /// Rotate 90 degrees
-    fn rotate(self) -> Self {
-        let mut out = [[false; SHAPE_SIZE]; SHAPE_SIZE];
-        for y in 0..SHAPE_SIZE {
-            for (x, row) in out.iter_mut().enumerate() {
-                row[SHAPE_SIZE - 1 - y] = self.0[y][x];
-            }
-        }
-        Shape(out)
+    fn rotate(&self) -> Self {
+        Self::new(self.tiles.iter().map(|(x, y)| (self.height - 1 - y, *x)))
     }
 
     /// Rotate by the required amount
-    fn rotate_to(self, rot: Rotation) -> Self {
+    fn rotate_to(&self, rot: Rotation) -> Self {
         match rot {
             Rotation::QuaterTurn => self.rotate(),
             Rotation::HalfTurn => self.rotate().rotate(),
@@ -91,28 +72,86 @@ impl Shape {
     }
 
     /// Mirror the shape along its x-axis
-    fn mirror(mut self) -> Self {
-        for row in &mut self.0 {
-            row.reverse();
-        }
-        self
+    fn mirror(&self) -> Self {
+        Self::new(self.tiles.iter().map(|(x, y)| (self.width - 1 - x, *y)))
+    }
+
+    /// Swap the rows and columns (mirror along the diagonal)
+    fn transpose(&self) -> Self {
+        Self::new(self.tiles.iter().map(|(x, y)| (*y, *x)))
     }
-}
 
-impl Ord for Shape {
-    /// Order shapes by their area
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.area().cmp(&other.area())
+    /// All the distinct orientations of this shape, which are the images of the shape by the symmetries of the square
+    /// (the dihedral group D4: 4 rotations, with or without mirroring)
+    fn orientations(&self) -> Vec<Self> {
+        let mut orientations = vec![
+            self.clone(),
+            self.rotate_to(Rotation::QuaterTurn),
+            self.rotate_to(Rotation::HalfTurn),
+            self.rotate_to(Rotation::ThreeQuarter),
+            self.mirror(),
+            self.rotate_to(Rotation::QuaterTurn).mirror(),
+            self.rotate_to(Rotation::HalfTurn).mirror(),
+            self.rotate_to(Rotation::ThreeQuarter).mirror(),
+        ];
+        // some might be duplicates (rotational symmetric or axisymmetrical parts), we should remove them
+        orientations.sort_unstable_by(|a, b| a.tiles.cmp(&b.tiles));
+        orientations.dedup();
+        orientations
     }
 }
 
-impl PartialOrd for Shape {
-    /// Order shapes by their area
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+/// A row of the region, as a bitmask where bit `x` is set if the tile at column `x` is occupied
+type Row = u128;
+
+/// The widest region we can pack
+const MAX_WIDTH: usize = Row::BITS as usize;
+
+/// Iterate over the positions of the set bits of a row
+fn bits(mut mask: Row) -> impl Iterator<Item = u32> {
+    std::iter::from_fn(move || {
+        let bit = mask.trailing_zeros();
+        mask &= mask.wrapping_sub(1);
+        (bit < Row::BITS).then_some(bit)
+    })
+}
+
+/// A shape in a fixed orientation, ready to be placed on a bitboard
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Orientation {
+    /// Bitmask of each row of the shape's bounding box
+    rows: Vec<Row>,
+    /// Width of the shape's bounding box
+    width: usize,
+    /// Column of the first tile of the top row, which is the one covering the cell we want to fill
+    anchor: usize,
+}
+
+impl From<&Shape> for Orientation {
+    fn from(shape: &Shape) -> Self {
+        let mut rows = vec![0; shape.height];
+        for (x, y) in &shape.tiles {
+            rows[*y] |= 1 << x;
+        }
+        Self {
+            rows,
+            width: shape.width,
+            anchor: shape.tiles.first().map_or(0, |(x, _)| *x),
+        }
     }
 }
 
+/// A kind of shape to place, with how many copies of it are left
+///
+/// Identical shapes are grouped together so that the search doesn't try permutations of them.
+#[derive(Debug, Clone)]
+struct Piece {
+    orientations: Vec<Orientation>,
+    area: usize,
+    /// Index of the shape of each copy left to place
+    shapes: Vec<usize>,
+}
+
 /// A region where to place presents under the tree
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Region {
@@ -121,6 +160,246 @@ pub struct Region {
     counts: Vec<usize>,
 }
 
+impl Display for Region {
+    /// Display the region as in the input
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}x{}: {}",
+            self.width,
+            self.height,
+            self.counts.iter().join(" ")
+        )
+    }
+}
+
+/// How a region was decided: with one of the cheap bounds, or with a full packing search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feasibility {
+    /// The pieces cover more tiles than there are in the region
+    Infeasible,
+    /// The region can be split into enough blocks to put each piece into its own
+    Trivial,
+    /// The bounds can't tell, the solver has to search for a packing
+    NeedsSearch,
+}
+
+impl Display for Feasibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Infeasible => "proven infeasible",
+            Self::Trivial => "trivially feasible",
+            Self::NeedsSearch => "needs search",
+        })
+    }
+}
+
+impl Region {
+    /// The shapes of the pieces to place, with their number of copies
+    fn pieces<'a>(
+        &'a self,
+        shapes: &'a [Shape],
+    ) -> impl Iterator<Item = (usize, &'a Shape, usize)> {
+        shapes
+            .iter()
+            .zip(&self.counts)
+            .enumerate()
+            .filter(|(_, (_, count))| **count > 0)
+            .map(|(i, (shape, count))| (i, shape, *count))
+    }
+
+    /// Size of the blocks to split the region into, if there are enough of them to give one to each piece
+    fn blocks(&self, shapes: &[Shape]) -> Option<(usize, usize)> {
+        let (short, long) = self
+            .pieces(shapes)
+            .fold((1, 1), |(short, long), (_, shape, _)| {
+                (
+                    short.max(shape.width.min(shape.height)),
+                    long.max(shape.width.max(shape.height)),
+                )
+            });
+        let pieces: usize = self.counts.iter().sum();
+        [(short, long), (long, short)]
+            .into_iter()
+            .find(|(width, height)| (self.width / width) * (self.height / height) >= pieces)
+    }
+
+    /// Decide the region with the area bound or by giving each piece its own block, without searching
+    pub fn classify(&self, shapes: &[Shape]) -> Feasibility {
+        let area: usize = self
+            .pieces(shapes)
+            .map(|(_, shape, count)| shape.area() * count)
+            .sum();
+        if area > self.width * self.height {
+            Feasibility::Infeasible
+        } else if self.blocks(shapes).is_some() {
+            Feasibility::Trivial
+        } else {
+            Feasibility::NeedsSearch
+        }
+    }
+
+    /// The packing putting each piece in its own block, which proves that the region is trivially feasible
+    fn block_packing(&self, shapes: &[Shape]) -> Option<Packing> {
+        let (width, height) = self.blocks(shapes)?;
+        let columns = self.width / width;
+        let placements = self
+            .pieces(shapes)
+            .flat_map(|(i, shape, count)| {
+                let orientation = shape
+                    .orientations()
+                    .iter()
+                    .position(|o| o.width <= width && o.height <= height)
+                    .expect("the blocks are big enough for every piece");
+                repeat_n((i, orientation), count)
+            })
+            .enumerate()
+            .map(|(block, (shape, orientation))| Placement {
+                shape,
+                orientation,
+                x: (block % columns) * width,
+                y: (block / columns) * height,
+            })
+            .collect();
+        Some(Packing {
+            width: self.width,
+            height: self.height,
+            placements,
+        })
+    }
+}
+
+/// A piece placed in a region
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placement {
+    /// Index of the shape in the puzzle input
+    pub shape: usize,
+    /// Index of the orientation in [`Shape::orientations`]
+    pub orientation: usize,
+    /// Column of the left-most tiles of the piece
+    pub x: usize,
+    /// Row of the top-most tiles of the piece
+    pub y: usize,
+}
+
+impl Placement {
+    /// The cells covered by the piece, as (x, y) coordinates in the region
+    fn cells(&self, shapes: &[Shape]) -> Vec<(usize, usize)> {
+        let orientation = Orientation::from(&shapes[self.shape].orientations()[self.orientation]);
+        orientation
+            .rows
+            .iter()
+            .enumerate()
+            .flat_map(|(dy, mask)| bits(*mask).map(move |dx| (self.x + dx as usize, self.y + dy)))
+            .collect()
+    }
+}
+
+/// How the shapes were packed into a region
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packing {
+    width: usize,
+    height: usize,
+    placements: Vec<Placement>,
+}
+
+/// Background colours (ANSI 256 colours palette) for the pieces in the terminal, cycled through
+const ANSI_COLORS: [u8; 12] = [160, 34, 178, 26, 127, 37, 166, 70, 98, 31, 204, 142];
+
+impl Packing {
+    /// Which piece (index in the placements) occupies each cell, row by row
+    fn grid(&self, shapes: &[Shape]) -> Vec<Vec<Option<usize>>> {
+        let mut grid = vec![vec![None; self.width]; self.height];
+        for (i, placement) in self.placements.iter().enumerate() {
+            for (x, y) in placement.cells(shapes) {
+                grid[y][x] = Some(i);
+            }
+        }
+        grid
+    }
+
+    /// Check that the pieces are within the region, don't overlap and match the region's shape counts
+    fn verify(&self, shapes: &[Shape], region: &Region) -> bool {
+        let mut counts = vec![0; region.counts.len()];
+        let mut occupied = vec![vec![false; region.width]; region.height];
+        for placement in &self.placements {
+            counts[placement.shape] += 1;
+            for (x, y) in placement.cells(shapes) {
+                if x >= region.width || y >= region.height || occupied[y][x] {
+                    return false;
+                }
+                occupied[y][x] = true;
+            }
+        }
+        counts == region.counts
+    }
+
+    /// Render the packing as text, with one letter per piece (and one background colour if `ansi` is set)
+    pub fn render(&self, shapes: &[Shape], ansi: bool) -> String {
+        let letter = |i: usize| {
+            let letters = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+            letters[i % letters.len()] as char
+        };
+        let mut out = String::new();
+        for row in self.grid(shapes) {
+            for cell in row {
+                match (cell, ansi) {
+                    (None, _) => out.push('.'),
+                    (Some(i), false) => out.push(letter(i)),
+                    (Some(i), true) => write!(
+                        out,
+                        "\x1b[30;48;5;{}m{}\x1b[0m",
+                        ANSI_COLORS[i % ANSI_COLORS.len()],
+                        letter(i)
+                    )
+                    .unwrap(),
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Export the packing as an SVG image, with one colour per piece
+    pub fn to_svg(&self, shapes: &[Shape]) -> String {
+        const CELL: usize = 20;
+        let (width, height) = (self.width * CELL, self.height * CELL);
+        let mut svg = String::from("<svg xmlns=\"http://www.w3.org/2000/svg\"");
+        writeln!(
+            svg,
+            " width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">"
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            "  <rect width=\"{width}\" height=\"{height}\" fill=\"#f4f4f4\" stroke=\"#888\"/>"
+        )
+        .unwrap();
+        for (i, placement) in self.placements.iter().enumerate() {
+            // spread the hues with the golden angle so that neighbours get different colours
+            let hue = (i * 137) % 360;
+            writeln!(
+                svg,
+                "  <g fill=\"hsl({hue}, 65%, 55%)\" stroke=\"hsl({hue}, 65%, 35%)\"><title>shape {}</title>",
+                placement.shape
+            )
+            .unwrap();
+            for (x, y) in placement.cells(shapes) {
+                writeln!(
+                    svg,
+                    "    <rect x=\"{}\" y=\"{}\" width=\"{CELL}\" height=\"{CELL}\"/>",
+                    x * CELL,
+                    y * CELL
+                )
+                .unwrap();
+            }
+            svg.push_str("  </g>\n");
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
 /// Today's puzzle input
 #[derive(Debug, Clone)]
 pub struct Puzzle {
@@ -128,68 +407,679 @@ pub struct Puzzle {
     regions: Vec<Region>,
 }
 
-/// Recursively try to pack all shapes into the region's grid
-fn pack(mut shapes_to_place: Vec<Shape>, mut grid: Vec<Vec<bool>>) -> bool {
-    let Some(shape) = shapes_to_place.pop() else {
-        // all shapes are placed, success!
-        return true;
-    };
-    // all possible orientations
-    let mut orientations = vec![
-        shape,
-        shape.rotate_to(Rotation::QuaterTurn),
-        shape.rotate_to(Rotation::HalfTurn),
-        shape.rotate_to(Rotation::ThreeQuarter),
-        shape.mirror(),
-        shape.rotate_to(Rotation::QuaterTurn).mirror(),
-        shape.rotate_to(Rotation::HalfTurn).mirror(),
-        shape.rotate_to(Rotation::ThreeQuarter).mirror(),
-    ];
-    // some might be duplicates (rotational symmetric or axisymmetrical parts), we should remove them
-    orientations.sort_unstable_by_key(|s| s.0);
-    orientations.dedup();
-    for s in orientations {
-        for x in 0..grid[0].len() {
-            for y in 0..grid.len() {
-                // try to place on the grid, at the first available spot
-                if !s.can_place(&grid, x, y) {
+impl Puzzle {
+    pub fn shapes(&self) -> &[Shape] {
+        &self.shapes
+    }
+
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+
+    /// Find how to pack the shapes into a region, with the solver selected by `DAY12_SOLVER`
+    pub fn pack(&self, region: &Region) -> Option<Packing> {
+        PackingSolver::from_env().solve(&self.shapes, region).1
+    }
+
+    /// Find the smallest region (or the smallest square) that fits the given number of copies of each shape
+    pub fn smallest_region(&self, counts: &[usize], square: bool) -> Option<(Region, Packing)> {
+        smallest_region(&self.shapes, counts, square, PackingSolver::from_env())
+    }
+}
+
+/// Backtracking search to pack shapes into a region, represented as a bitboard
+///
+/// The search always considers the first empty cell (in reading order): either a piece is placed so that its first
+/// tile covers it, or the cell is left empty for good. Leaving cells empty is only allowed while there is more free
+/// space than the area of the pieces left to place, which prunes most dead ends early.
+#[derive(Debug, Clone)]
+struct Packer {
+    width: usize,
+    height: usize,
+    /// Occupied (or given up) cells, one bitmask per row
+    grid: Vec<Row>,
+    pieces: Vec<Piece>,
+    /// Number of cells which are still empty
+    free: usize,
+    /// Total area of the pieces left to place
+    needed: usize,
+    /// Height of the tallest piece, which is how far below the first empty cell the grid can be filled
+    depth: usize,
+    /// States which are known to be dead ends, see [`Packer::state`]
+    failed: HashSet<State>,
+    /// The pieces placed so far
+    placed: Vec<Placement>,
+}
+
+/// The state of the search, as the first row with empty cells, the content of the rows which can be partially filled
+/// starting from it, and the number of pieces of each kind left to place
+type State = (usize, Vec<Row>, Vec<usize>);
+
+impl Packer {
+    /// Prepare the search for placing `counts[i]` copies of `shapes[i]` in a `width` x `height` region
+    fn new(shapes: &[Shape], counts: &[usize], width: usize, height: usize) -> Self {
+        assert!(width <= MAX_WIDTH, "region is too wide");
+        let mut pieces: Vec<Piece> = Vec::new();
+        for (index, (shape, count)) in shapes.iter().zip(counts).enumerate() {
+            if *count == 0 {
+                continue;
+            }
+            let copies = repeat_n(index, *count);
+            let orientations: Vec<Orientation> =
+                shape.orientations().iter().map(Into::into).collect();
+            // identical shapes (up to rotation and mirroring) have the same set of orientations
+            if let Some(piece) = pieces.iter_mut().find(|p| p.orientations == orientations) {
+                piece.shapes.extend(copies);
+                continue;
+            }
+            pieces.push(Piece {
+                orientations,
+                area: shape.area(),
+                shapes: copies.collect(),
+            });
+        }
+        // try the largest pieces first
+        pieces.sort_by_key(|p| std::cmp::Reverse(p.area));
+        let depth = pieces
+            .iter()
+            .flat_map(|p| &p.orientations)
+            .map(|o| o.rows.len())
+            .max()
+            .unwrap_or(0);
+        Self {
+            depth,
+            failed: HashSet::new(),
+            placed: Vec::new(),
+            width,
+            height,
+            grid: vec![0; height],
+            needed: pieces.iter().map(|p| p.area * p.shapes.len()).sum(),
+            pieces,
+            free: width * height,
+        }
+    }
+
+    /// Check whether the orientation can be placed with its anchor tile at (x, y)
+    fn fits(&self, orientation: &Orientation, x: usize, y: usize) -> bool {
+        let Some(left) = x.checked_sub(orientation.anchor) else {
+            return false;
+        };
+        left + orientation.width <= self.width
+            && y + orientation.rows.len() <= self.height
+            && orientation
+                .rows
+                .iter()
+                .zip(&self.grid[y..])
+                .all(|(mask, row)| (mask << left) & row == 0)
+    }
+
+    /// Place or remove (the operation is its own inverse) the orientation with its anchor tile at (x, y)
+    fn toggle(&mut self, orientation: &Orientation, x: usize, y: usize) {
+        let left = x - orientation.anchor;
+        for (mask, row) in orientation.rows.iter().zip(&mut self.grid[y..]) {
+            *row ^= mask << left;
+        }
+    }
+
+    /// Find the first empty cell, starting the scan at row `y`
+    fn first_empty(&self, y: usize) -> Option<(usize, usize)> {
+        let full = Row::MAX >> (MAX_WIDTH - self.width);
+        (y..self.height).find_map(|y| {
+            let empty = !self.grid[y] & full;
+            (empty != 0).then(|| (empty.trailing_zeros() as usize, y))
+        })
+    }
+
+    /// Count the empty cells near row `y` which no remaining piece can cover anymore
+    ///
+    /// All the rows before `y` are full, so the pieces placed from now on have their top row at `y` or below. We only
+    /// look at the rows which can be reached by a piece starting at `y`, since the ones below are mostly empty.
+    fn dead_cells(&self, y: usize) -> usize {
+        let full = Row::MAX >> (MAX_WIDTH - self.width);
+        let end = (y + self.depth).min(self.height);
+        let mut coverable = vec![0; end - y];
+        for piece in self.pieces.iter().filter(|p| !p.shapes.is_empty()) {
+            for orientation in &piece.orientations {
+                if orientation.width > self.width {
+                    continue;
+                }
+                let lefts = Row::MAX >> (MAX_WIDTH - (self.width - orientation.width + 1));
+                for top in y..end {
+                    if top + orientation.rows.len() > self.height {
+                        break;
+                    }
+                    // find all the columns where the orientation fits at once: a tile at `dx` blocks the positions
+                    // where it would land on an occupied cell
+                    let mut blocked = 0;
+                    for (mask, row) in orientation.rows.iter().zip(&self.grid[top..]) {
+                        for dx in bits(*mask) {
+                            blocked |= row >> dx;
+                        }
+                    }
+                    let valid = lefts & !blocked;
+                    if valid == 0 {
+                        continue;
+                    }
+                    for (mask, cov) in orientation.rows.iter().zip(&mut coverable[top - y..]) {
+                        for dx in bits(*mask) {
+                            *cov |= valid << dx;
+                        }
+                    }
+                }
+            }
+        }
+        coverable
+            .iter()
+            .zip(&self.grid[y..end])
+            .map(|(cov, row)| (!row & !cov & full).count_ones() as usize)
+            .sum()
+    }
+
+    /// The current state of the search, given that all the rows before `y` are full
+    ///
+    /// The pieces placed so far can't reach further than `depth` rows below `y`, so the rows after that are empty and
+    /// the state captures everything that matters for placing the remaining pieces. Different orders of placements
+    /// often lead to the same state, which we only need to explore once.
+    fn state(&self, y: usize) -> State {
+        let end = (y + self.depth).min(self.height);
+        (
+            y,
+            self.grid[y..end].to_vec(),
+            self.pieces.iter().map(|p| p.shapes.len()).collect(),
+        )
+    }
+
+    /// Try to place all the remaining pieces, knowing that all the rows before `y` are full
+    fn search(&mut self, y: usize) -> bool {
+        if self.needed == 0 {
+            return true;
+        }
+        if self.needed > self.free {
+            return false;
+        }
+        let Some((x, y)) = self.first_empty(y) else {
+            return false;
+        };
+        if self.dead_cells(y) > self.free - self.needed {
+            return false;
+        }
+        let state = self.state(y);
+        if self.failed.contains(&state) {
+            return false;
+        }
+        for p in 0..self.pieces.len() {
+            if self.pieces[p].shapes.is_empty() {
+                continue;
+            }
+            for o in 0..self.pieces[p].orientations.len() {
+                let orientation = &self.pieces[p].orientations[o];
+                if !self.fits(orientation, x, y) {
                     continue;
                 }
-                s.place(&mut grid, x, y);
-                // try to place all other parts recursively
-                if pack(shapes_to_place.clone(), grid.clone()) {
-                    // all shapes are placed, success!
+                let orientation = orientation.clone();
+                let area = self.pieces[p].area;
+                self.toggle(&orientation, x, y);
+                let shape = self.pieces[p].shapes.pop().unwrap();
+                self.placed.push(Placement {
+                    shape,
+                    orientation: o,
+                    x: x - orientation.anchor,
+                    y,
+                });
+                self.free -= area;
+                self.needed -= area;
+                if self.search(y) {
                     return true;
                 }
-                // we couldn't fit all the other parts, we have to continue trying
-                s.remove(&mut grid, x, y); // remove the shape we just placed to try other orientations
+                self.toggle(&orientation, x, y);
+                self.placed.pop();
+                self.pieces[p].shapes.push(shape);
+                self.free += area;
+                self.needed += area;
+            }
+        }
+        // leave the cell empty, if we can afford it
+        if self.free > self.needed {
+            self.grid[y] |= 1 << x;
+            self.free -= 1;
+            if self.search(y) {
+                return true;
+            }
+            self.grid[y] &= !(1 << x);
+            self.free += 1;
+        }
+        self.failed.insert(state);
+        false
+    }
+}
+
+/// Find how to pack all the shapes into the region, if possible
+///
+/// Pieces can be rotated, so we can pack the transposed region instead, which makes the search frontier (the few rows
+/// being filled) smaller when the region is wider than tall.
+fn pack(shapes: &[Shape], region: &Region) -> Option<Packing> {
+    let transposed = region.width > region.height;
+    let (width, height) = if transposed {
+        (region.height, region.width)
+    } else {
+        (region.width, region.height)
+    };
+    let mut packer = Packer::new(shapes, &region.counts, width, height);
+    if !packer.search(0) {
+        return None;
+    }
+    let mut placements = packer.placed;
+    if transposed {
+        for placement in &mut placements {
+            let orientations = shapes[placement.shape].orientations();
+            let flipped = orientations[placement.orientation].transpose();
+            placement.orientation = orientations
+                .iter()
+                .position(|o| *o == flipped)
+                .expect("the transposed shape should be one of its orientations");
+            (placement.x, placement.y) = (placement.y, placement.x);
+        }
+    }
+    Some(Packing {
+        width: region.width,
+        height: region.height,
+        placements,
+    })
+}
+
+/// The root of the columns list in [`Dlx`]
+const ROOT: usize = 0;
+
+/// Sparse matrix for Knuth's Algorithm X with dancing links
+///
+/// Primary columns must be covered exactly once, secondary columns at most once. Node 0 is the root, the next nodes are
+/// the column headers and the rest are the 1s of the matrix, linked in circular lists horizontally (the row) and
+/// vertically (the column).
+#[derive(Debug, Clone)]
+struct Dlx {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    /// Header of the column of each node
+    column: Vec<usize>,
+    /// Row index of each node
+    row: Vec<usize>,
+    /// Number of nodes in each column (only meaningful for headers)
+    size: Vec<usize>,
+    rows: usize,
+    primary: usize,
+    /// Whether each column is covered by a row of the partial solution (only meaningful for secondary headers)
+    used: Vec<bool>,
+    /// How many secondary columns are allowed to stay uncovered
+    slack: usize,
+}
+
+impl Dlx {
+    /// Create an empty matrix with the given number of primary and secondary columns
+    ///
+    /// At most `slack` secondary columns can stay uncovered in a solution, which allows pruning the search when too
+    /// many of them can't be covered anymore.
+    fn new(primary: usize, secondary: usize, slack: usize) -> Self {
+        let headers = primary + secondary + 1;
+        let mut dlx = Self {
+            left: (0..headers).collect(),
+            right: (0..headers).collect(),
+            up: (0..headers).collect(),
+            down: (0..headers).collect(),
+            column: (0..headers).collect(),
+            row: vec![usize::MAX; headers],
+            size: vec![0; headers],
+            rows: 0,
+            primary,
+            used: vec![false; headers],
+            slack,
+        };
+        // only the primary columns are linked to the root, secondary columns are never chosen
+        for c in 0..=primary {
+            dlx.right[c] = (c + 1) % (primary + 1);
+            dlx.left[(c + 1) % (primary + 1)] = c;
+        }
+        dlx
+    }
+
+    /// Add a row with 1s in the given columns (0-based, primary columns first) and return its index
+    fn add_row(&mut self, columns: &[usize]) -> usize {
+        let mut first = None;
+        for c in columns {
+            let header = c + 1;
+            let node = self.column.len();
+            self.up.push(self.up[header]);
+            self.down.push(header);
+            self.down[self.up[header]] = node;
+            self.up[header] = node;
+            self.size[header] += 1;
+            self.column.push(header);
+            self.row.push(self.rows);
+            self.size.push(0);
+            match first {
+                None => {
+                    self.left.push(node);
+                    self.right.push(node);
+                    first = Some(node);
+                }
+                Some(first) => {
+                    self.left.push(self.left[first]);
+                    self.right.push(first);
+                    self.right[self.left[first]] = node;
+                    self.left[first] = node;
+                }
+            }
+        }
+        self.rows += 1;
+        self.rows - 1
+    }
+
+    /// Remove a column and all the rows which intersect it
+    fn cover(&mut self, c: usize) {
+        self.right[self.left[c]] = self.right[c];
+        self.left[self.right[c]] = self.left[c];
+        let mut i = self.down[c];
+        while i != c {
+            let mut j = self.right[i];
+            while j != i {
+                self.up[self.down[j]] = self.up[j];
+                self.down[self.up[j]] = self.down[j];
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
             }
+            i = self.down[i];
         }
     }
-    false
+
+    /// Undo [`Dlx::cover`], in the reverse order
+    fn uncover(&mut self, c: usize) {
+        let mut i = self.up[c];
+        while i != c {
+            let mut j = self.left[i];
+            while j != i {
+                self.size[self.column[j]] += 1;
+                self.up[self.down[j]] = j;
+                self.down[self.up[j]] = j;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+        self.right[self.left[c]] = c;
+        self.left[self.right[c]] = c;
+    }
+
+    /// Find a set of rows covering all primary columns exactly once, and secondary columns at most once
+    ///
+    /// `allow` can reject a row given the rows already in the partial solution, for symmetry breaking. The found rows
+    /// are left in `solution`.
+    fn search(
+        &mut self,
+        solution: &mut Vec<usize>,
+        allow: &impl Fn(&[usize], usize) -> bool,
+    ) -> bool {
+        if self.right[ROOT] == ROOT {
+            return true;
+        }
+        let dead = (self.primary + 1..self.used.len())
+            .filter(|c| !self.used[*c] && self.size[*c] == 0)
+            .count();
+        if dead > self.slack {
+            return false;
+        }
+        // choose the column with the fewest options
+        let mut c = self.right[ROOT];
+        let mut j = self.right[c];
+        while j != ROOT {
+            if self.size[j] < self.size[c] {
+                c = j;
+            }
+            j = self.right[j];
+        }
+        if self.size[c] == 0 {
+            return false;
+        }
+        self.cover(c);
+        let mut r = self.down[c];
+        while r != c {
+            if allow(solution, self.row[r]) {
+                solution.push(self.row[r]);
+                let mut j = self.right[r];
+                while j != r {
+                    self.cover(self.column[j]);
+                    self.used[self.column[j]] = true;
+                    j = self.right[j];
+                }
+                if self.search(solution, allow) {
+                    return true;
+                }
+                let mut j = self.left[r];
+                while j != r {
+                    self.uncover(self.column[j]);
+                    self.used[self.column[j]] = false;
+                    j = self.left[j];
+                }
+                solution.pop();
+            }
+            r = self.down[r];
+        }
+        self.uncover(c);
+        false
+    }
+}
+
+/// Find how to pack all the shapes into the region, as an exact cover problem solved with [`Dlx`]
+///
+/// Each shape instance is a primary column (it must be placed) and each cell of the region a secondary column (it can
+/// stay empty). There is one row per instance, orientation and position in the region. Instances of the same shape
+/// are interchangeable, so we only accept solutions where they use placements in increasing order.
+///
+/// The matrix gets very large for big regions with many pieces, so this is mostly useful for checking the
+/// backtracking search of [`Packer`] on smaller regions.
+fn pack_dlx(shapes: &[Shape], region: &Region) -> Option<Packing> {
+    let (width, height) = (region.width, region.height);
+    let instances: Vec<usize> = region
+        .counts
+        .iter()
+        .enumerate()
+        .flat_map(|(shape, count)| repeat_n(shape, *count))
+        .collect();
+    let total_area: usize = instances.iter().map(|s| shapes[*s].area()).sum();
+    if total_area > width * height {
+        return None;
+    }
+    // each possible placement of each shape, with the cells it covers
+    let placements: Vec<Vec<(Placement, Vec<usize>)>> = shapes
+        .iter()
+        .enumerate()
+        .map(|(index, shape)| {
+            let mut placements = Vec::new();
+            for (o, orientation) in shape.orientations().into_iter().enumerate() {
+                let orientation = Orientation::from(&orientation);
+                if orientation.width > width || orientation.rows.len() > height {
+                    continue;
+                }
+                for y in 0..=(height - orientation.rows.len()) {
+                    for x in 0..=(width - orientation.width) {
+                        let placement = Placement {
+                            shape: index,
+                            orientation: o,
+                            x,
+                            y,
+                        };
+                        let cells = orientation
+                            .rows
+                            .iter()
+                            .enumerate()
+                            .flat_map(|(dy, mask)| {
+                                bits(*mask).map(move |dx| (y + dy) * width + x + dx as usize)
+                            })
+                            .collect();
+                        placements.push((placement, cells));
+                    }
+                }
+            }
+            placements
+        })
+        .collect();
+    let mut dlx = Dlx::new(instances.len(), width * height, width * height - total_area);
+    // instance and placement index of each row
+    let mut rows = Vec::new();
+    for (instance, shape) in instances.iter().enumerate() {
+        for (p, (_, cells)) in placements[*shape].iter().enumerate() {
+            let columns: Vec<_> = std::iter::once(instance)
+                .chain(cells.iter().map(|c| instances.len() + c))
+                .collect();
+            dlx.add_row(&columns);
+            rows.push((instance, p));
+        }
+    }
+    let allow = |solution: &[usize], row: usize| {
+        let (instance, p) = rows[row];
+        solution.iter().all(|other| {
+            let (other_instance, other_p) = rows[*other];
+            instances[other_instance] != instances[instance]
+                || (other_instance < instance) == (other_p < p)
+        })
+    };
+    let mut solution = Vec::new();
+    if !dlx.search(&mut solution, &allow) {
+        return None;
+    }
+    Some(Packing {
+        width,
+        height,
+        placements: solution
+            .into_iter()
+            .map(|row| {
+                let (instance, p) = rows[row];
+                placements[instances[instance]][p].0.clone()
+            })
+            .collect(),
+    })
+}
+
+/// Which algorithm to use for packing the shapes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackingSolver {
+    /// Backtracking search on a bitboard, see [`Packer`]
+    Backtracking,
+    /// Exact cover with dancing links, see [`Dlx`]
+    Dlx,
+}
+
+impl PackingSolver {
+    /// Select the solver with the `DAY12_SOLVER` environment variable (`backtracking` by default, or `dlx`)
+    fn from_env() -> Self {
+        match std::env::var("DAY12_SOLVER").as_deref() {
+            Ok("dlx") => Self::Dlx,
+            _ => Self::Backtracking,
+        }
+    }
+
+    /// Find how to pack all the shapes into the region, if possible
+    fn pack(self, shapes: &[Shape], region: &Region) -> Option<Packing> {
+        match self {
+            Self::Backtracking => pack(shapes, region),
+            Self::Dlx => pack_dlx(shapes, region),
+        }
+    }
+
+    /// Find how to pack all the shapes into the region, only searching if the cheap bounds can't decide
+    fn solve(self, shapes: &[Shape], region: &Region) -> (Feasibility, Option<Packing>) {
+        let feasibility = region.classify(shapes);
+        let packing = match feasibility {
+            Feasibility::Infeasible => None,
+            Feasibility::Trivial => region.block_packing(shapes),
+            Feasibility::NeedsSearch => self.pack(shapes, region),
+        };
+        (feasibility, packing)
+    }
+}
+
+/// Try the regions by increasing area, starting from the total area of the pieces, until one fits them all
+///
+/// Regions are always at most as wide as they are high, and the squarest one comes first among regions with the same
+/// area. Returns `None` if there are no pieces, or if they only fit in squares too big for the solvers.
+fn smallest_region(
+    shapes: &[Shape],
+    counts: &[usize],
+    square: bool,
+    solver: PackingSolver,
+) -> Option<(Region, Packing)> {
+    let pieces = || shapes.iter().zip(counts).filter(|(_, count)| **count > 0);
+    // every piece has to fit, lying with its shortest side along the width
+    let (min_width, min_height) = pieces().fold((0, 0), |(width, height), (shape, _)| {
+        (
+            width.max(shape.width.min(shape.height)),
+            height.max(shape.width.max(shape.height)),
+        )
+    });
+    if min_width == 0 {
+        return None;
+    }
+    let area: usize = pieces().map(|(shape, count)| shape.area() * count).sum();
+    // stacking all the pieces on top of each other always works
+    let max_height: usize = pieces()
+        .map(|(shape, count)| shape.width.max(shape.height) * count)
+        .sum();
+    let candidates: Box<dyn Iterator<Item = (usize, usize)>> = if square {
+        Box::new(
+            (min_height.max(area.isqrt())..=max_height)
+                .map(|side| (side, side))
+                .filter(|(width, height)| width * height >= area),
+        )
+    } else {
+        Box::new(
+            (area..=min_width * max_height)
+                .flat_map(|area| {
+                    (min_width..=area.isqrt())
+                        .rev()
+                        .filter(move |width| area % width == 0)
+                        .map(move |width| (width, area / width))
+                })
+                .filter(|(_, height)| *height >= min_height),
+        )
+    };
+    candidates
+        .filter(|(width, _)| *width <= MAX_WIDTH)
+        .find_map(|(width, height)| {
+            let region = Region {
+                width,
+                height,
+                counts: counts.to_vec(),
+            };
+            let packing = solver.solve(shapes, &region).1?;
+            Some((region, packing))
+        })
 }
 
 pub struct Day12;
 
+/// Parse the rows of a shape, which can have any size (as long as it fits in a region's row)
 fn parse_shape(input: &mut &str) -> Result<Shape> {
-    let tiles: Vec<Vec<bool>> = separated(
-        SHAPE_SIZE,
-        repeat::<_, _, Vec<_>, _, _>(SHAPE_SIZE, one_of(('#', '.')).map(|c: char| c == '#')),
+    let rows: Vec<Vec<bool>> = separated(
+        1..,
+        repeat::<_, _, Vec<_>, _, _>(1.., one_of(('#', '.')).map(|c: char| c == '#')),
         newline,
     )
     .parse_next(input)?;
-    Ok(Shape(
-        tiles
-            .into_iter()
-            .map(|row| row.try_into().unwrap())
-            .collect_vec()
-            .try_into()
-            .unwrap(),
-    ))
+    let shape = Shape::new(rows.iter().enumerate().flat_map(|(y, row)| {
+        row.iter()
+            .enumerate()
+            .filter(|(_, tile)| **tile)
+            .map(move |(x, _)| (x, y))
+    }));
+    Ok(shape)
 }
 
 fn parse_shape_definition(input: &mut &str) -> Result<Shape> {
-    delimited((dec_uint::<_, u8, _>, ':', newline), parse_shape, newline).parse_next(input)
+    delimited(
+        (dec_uint::<_, u8, _>, ':', newline),
+        parse_shape.verify(|shape: &Shape| shape.area() > 0 && shape.width <= MAX_WIDTH),
+        newline,
+    )
+    .parse_next(input)
 }
 
 fn parse_all_shapes(input: &mut &str) -> Result<Vec<Shape>> {
@@ -201,71 +1091,331 @@ fn parse_dimensions(input: &mut &str) -> Result<(usize, usize)> {
     Ok((width, height))
 }
 
-fn parse_region(input: &mut &str) -> Result<Region> {
-    let ((width, height), _, counts) = (
-        parse_dimensions,
-        ": ",
-        separated(1.., dec_uint::<_, usize, _>, ' '),
-    )
-        .parse_next(input)?;
-    Ok(Region {
-        width,
-        height,
-        counts,
-    })
+/// Parse a region, with one count for each of the `shapes`
+fn parse_region(shapes: usize) -> impl FnMut(&mut &str) -> Result<Region> {
+    move |input| {
+        let ((width, height), _, counts) = (
+            parse_dimensions.verify(|(width, height)| *width.min(height) <= MAX_WIDTH),
+            ": ",
+            separated(1.., dec_uint::<_, usize, _>, ' ')
+                .verify(|counts: &Vec<usize>| counts.len() == shapes),
+        )
+            .parse_next(input)?;
+        Ok(Region {
+            width,
+            height,
+            counts,
+        })
+    }
 }
 
-fn parse_all_regions(input: &mut &str) -> Result<Vec<Region>> {
-    separated(1.., parse_region, newline).parse_next(input)
+fn parse_all_regions(shapes: usize) -> impl FnMut(&mut &str) -> Result<Vec<Region>> {
+    move |input| separated(1.., parse_region(shapes), newline).parse_next(input)
 }
 
 impl Day for Day12 {
-    type Input = Puzzle;
+    type Input<'a> = Puzzle;
 
-    fn parser(input: &mut &str) -> Result<Self::Input> {
-        seq! { Puzzle{
-            shapes: parse_all_shapes,
-            _: newline,
-            regions: parse_all_regions
-        }}
-        .parse_next(input)
+    fn parser<'a>(input: &mut &'a str) -> Result<Self::Input<'a>> {
+        let shapes = parse_all_shapes.parse_next(input)?;
+        let regions = preceded(newline, parse_all_regions(shapes.len())).parse_next(input)?;
+        Ok(Puzzle { shapes, regions })
     }
 
     type Output1 = usize;
 
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        let mut count = 0;
-        for region in &input.regions {
-            let mut shapes_to_place = Vec::new();
-            // search with largest shapes first (= put them at the end of the list)
-            for (count, shape) in region
-                .counts
-                .iter()
-                .zip(input.shapes.iter())
-                .sorted_unstable_by(|(_, a), (_, b)| a.cmp(b))
-            {
-                shapes_to_place.extend(repeat_n(*shape, *count));
-            }
+    fn part_1(input: &Self::Input<'_>) -> Self::Output1 {
+        let solver = PackingSolver::from_env();
+        input
+            .regions
+            .iter()
+            .filter(|region| {
+                let (_, packing) = solver.solve(&input.shapes, region);
+                if let Some(packing) = &packing {
+                    debug_assert!(packing.verify(&input.shapes, region));
+                }
+                packing.is_some()
+            })
+            .count()
+    }
 
-            // necessary optimization, some regions are too small to fit all shapes
-            let total_area: usize = shapes_to_place.iter().map(Shape::area).sum();
-            if total_area > region.width * region.height {
-                continue; // impossible to pack, not enough space
-            }
+    type Output2 = usize;
+
+    fn part_2(_input: &Self::Input<'_>) -> Self::Output2 {
+        0
+    }
+
+    const PART_2_PLACEHOLDER: bool = true;
+
+    fn explain(input: &Self::Input<'_>) -> Option<String> {
+        let solver = PackingSolver::from_env();
+        let mut out = String::new();
+        // (regions, regions which fit) for each class
+        let mut stats = [(0, 0); 3];
+        for (i, region) in input.regions.iter().enumerate() {
+            let (feasibility, packing) = solver.solve(&input.shapes, region);
+            let stat = &mut stats[feasibility as usize];
+            stat.0 += 1;
+            stat.1 += usize::from(packing.is_some());
+            let result = if packing.is_some() {
+                "fits"
+            } else {
+                "doesn't fit"
+            };
+            writeln!(out, "Region {} ({region}): {feasibility}, {result}", i + 1).unwrap();
+        }
+        for (feasibility, (regions, fit)) in [
+            Feasibility::Infeasible,
+            Feasibility::Trivial,
+            Feasibility::NeedsSearch,
+        ]
+        .into_iter()
+        .zip(stats)
+        {
+            write!(out, "\n{feasibility}: {regions} regions, {fit} fit").unwrap();
+        }
+        Some(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::days::TestRng;
+
+    const INPUT: &str = "0:
+###
+##.
+##.
+
+1:
+###
+##.
+.##
+
+2:
+.##
+###
+##.
+
+3:
+##.
+###
+##.
+
+4:
+###
+#..
+###
+
+5:
+###
+.#.
+###
 
-            if pack(
-                shapes_to_place,
-                vec![vec![false; region.width]; region.height],
-            ) {
-                count += 1;
+4x4: 0 0 0 0 2 0
+12x5: 1 0 1 0 2 2
+12x5: 1 0 1 0 3 2";
+
+    #[test]
+    fn test_part1() {
+        let parsed = Day12::parse(INPUT).unwrap();
+        assert_eq!(Day12::part_1(&parsed), 2);
+    }
+
+    #[test]
+    fn test_pack_dense() {
+        let parsed = Day12::parse(INPUT).unwrap();
+        // two copies of shape 0 fit in 5x3 with a single cell to spare
+        let region = Region {
+            width: 5,
+            height: 3,
+            counts: vec![2, 0, 0, 0, 0, 0],
+        };
+        assert!(pack(&parsed.shapes, &region).is_some());
+        // enough room, but too narrow for the shape
+        let region = Region {
+            width: 7,
+            height: 2,
+            counts: vec![1, 0, 0, 0, 0, 0],
+        };
+        assert!(pack(&parsed.shapes, &region).is_none());
+        // identical shapes (up to rotation) are grouped
+        let shape = &parsed.shapes[0];
+        let packer = Packer::new(&[shape.clone(), shape.rotate()], &[1, 2], 6, 3);
+        assert_eq!(packer.pieces.len(), 1);
+        assert_eq!(packer.pieces[0].shapes, vec![0, 1, 1]);
+    }
+
+    #[test]
+    fn test_solvers_agree() {
+        let parsed = Day12::parse(INPUT).unwrap();
+        // the last region takes a few seconds to refute with DLX in debug builds
+        for region in &parsed.regions[..2] {
+            assert_eq!(
+                PackingSolver::Backtracking
+                    .pack(&parsed.shapes, region)
+                    .is_some(),
+                PackingSolver::Dlx.pack(&parsed.shapes, region).is_some()
+            );
+        }
+        // pseudo-random small regions, some of them tight
+        let mut rng = TestRng::new(42);
+        for _ in 0..40 {
+            let width = 3 + rng.below(4);
+            let height = 3 + rng.below(4);
+            let mut counts = vec![0; parsed.shapes.len()];
+            for _ in 0..(width * height / 9 + rng.below(2)) {
+                counts[rng.below(parsed.shapes.len())] += 1;
+            }
+            let region = Region {
+                width,
+                height,
+                counts,
+            };
+            let backtracking = PackingSolver::Backtracking.pack(&parsed.shapes, &region);
+            let dlx = PackingSolver::Dlx.pack(&parsed.shapes, &region);
+            assert_eq!(backtracking.is_some(), dlx.is_some(), "{region:?}");
+            for packing in backtracking.iter().chain(&dlx) {
+                assert!(packing.verify(&parsed.shapes, &region), "{region:?}");
             }
         }
-        count
     }
 
-    type Output2 = usize;
+    #[test]
+    fn test_render() {
+        let parsed = Day12::parse(INPUT).unwrap();
+        let region = &parsed.regions()[1];
+        for solver in [PackingSolver::Backtracking, PackingSolver::Dlx] {
+            let packing = solver.pack(parsed.shapes(), region).unwrap();
+            assert!(packing.verify(parsed.shapes(), region));
+            let text = packing.render(parsed.shapes(), false);
+            assert_eq!(text.lines().count(), 5);
+            assert!(text.lines().all(|line| line.len() == 12));
+            // 6 pieces of 7 tiles each
+            assert_eq!(text.chars().filter(|c| *c == '.').count(), 60 - 6 * 7);
+            assert_eq!(text.chars().filter(|c| *c == 'F').count(), 7);
+            assert!(
+                packing
+                    .render(parsed.shapes(), true)
+                    .contains("\x1b[30;48;5;")
+            );
+            let svg = packing.to_svg(parsed.shapes());
+            assert!(svg.starts_with("<svg "));
+            assert_eq!(svg.matches("<g ").count(), 6);
+        }
+    }
 
-    fn part_2(_input: &Self::Input) -> Self::Output2 {
-        0
+    #[test]
+    fn test_polyominoes() {
+        let input = "0:
+#####
+
+1:
+.#
+##
+#.
+#.
+
+2:
+##
+##
+
+5x2: 2 0 0
+4x3: 0 1 1
+4x3: 0 0 3";
+        let parsed = Day12::parse(input).unwrap();
+        assert_eq!(parsed.shapes[1].width, 2);
+        assert_eq!(parsed.shapes[1].height, 4);
+        // symmetries: the straight line has 2 orientations, the N-pentomino 8 and the square only 1
+        let orientations: Vec<_> = parsed
+            .shapes
+            .iter()
+            .map(|s| s.orientations().len())
+            .collect();
+        assert_eq!(orientations, [2, 8, 1]);
+        for solver in [PackingSolver::Backtracking, PackingSolver::Dlx] {
+            let results: Vec<_> = parsed
+                .regions
+                .iter()
+                .map(|region| solver.pack(&parsed.shapes, region).is_some())
+                .collect();
+            assert_eq!(results, [true, true, false]);
+        }
+    }
+
+    #[test]
+    fn test_smallest_region() {
+        let parsed = Day12::parse(INPUT).unwrap();
+        let smallest = |shapes: &[Shape], counts: &[usize], square| {
+            [PackingSolver::Backtracking, PackingSolver::Dlx].map(|solver| {
+                smallest_region(shapes, counts, square, solver).map(|(region, packing)| {
+                    assert!(packing.verify(shapes, &region));
+                    (region.width, region.height)
+                })
+            })
+        };
+        assert_eq!(
+            smallest(&parsed.shapes, &[1, 0, 0, 0, 0, 0], false),
+            [Some((3, 3)); 2]
+        );
+        // two copies of shape 4 don't fit in 3x5, even though they would cover only 14 tiles
+        assert_eq!(
+            smallest(&parsed.shapes, &[0, 0, 0, 0, 2, 0], false),
+            [Some((4, 4)); 2]
+        );
+        assert_eq!(smallest(&parsed.shapes, &[0; 6], false), [None; 2]);
+
+        let parsed = Day12::parse("0:\n#####\n\n1x1: 0").unwrap();
+        assert_eq!(smallest(&parsed.shapes, &[2], false), [Some((2, 5)); 2]);
+        assert_eq!(smallest(&parsed.shapes, &[2], true), [Some((5, 5)); 2]);
+    }
+
+    #[test]
+    fn test_region_counts() {
+        // one count per shape
+        assert!(Day12::parse("0:\n#####\n\n5x5: 1").is_ok());
+        assert!(Day12::parse("0:\n#####\n\n5x5: 1 1").is_err());
+        assert!(Day12::parse("0:\n#####\n\n1:\n##\n\n5x5: 1 1").is_ok());
+        assert!(Day12::parse("0:\n#####\n\n1:\n##\n\n5x5: 1").is_err());
+    }
+
+    #[test]
+    fn test_classify() {
+        let parsed = Day12::parse(INPUT).unwrap();
+        let classes: Vec<_> = parsed
+            .regions
+            .iter()
+            .map(|region| region.classify(&parsed.shapes))
+            .collect();
+        assert_eq!(classes, [Feasibility::NeedsSearch; 3]);
+
+        let parsed = Day12::parse(&format!(
+            "{}\n\n6x6: 1 1 1 1 0 0\n3x3: 0 0 0 0 2 0\n5x4: 0 0 0 0 0 0",
+            INPUT.split("\n\n").take(6).join("\n\n")
+        ))
+        .unwrap();
+        let classes: Vec<_> = parsed
+            .regions
+            .iter()
+            .map(|region| PackingSolver::Backtracking.solve(&parsed.shapes, region))
+            .collect();
+        assert_eq!(classes[0].0, Feasibility::Trivial);
+        assert!(
+            classes[0]
+                .1
+                .as_ref()
+                .unwrap()
+                .verify(&parsed.shapes, &parsed.regions[0])
+        );
+        assert_eq!(classes[1], (Feasibility::Infeasible, None));
+        assert_eq!(classes[2].0, Feasibility::Trivial);
+
+        // the line and the N-pentomino need 5x2 blocks, laid out on top of each other
+        let parsed = Day12::parse("0:\n#####\n\n1:\n.#\n##\n#.\n#.\n\n5x4: 1 1").unwrap();
+        let region = &parsed.regions[0];
+        assert_eq!(region.classify(&parsed.shapes), Feasibility::Trivial);
+        let packing = region.block_packing(&parsed.shapes).unwrap();
+        assert!(packing.verify(&parsed.shapes, region));
     }
 }
@@ -0,0 +1,275 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+    fmt::Write as _,
+    fs,
+    path::Path,
+};
+
+use anyhow::{Context as _, Result, bail};
+use chrono::{DateTime, Local, TimeZone as _, Utc};
+use serde::Deserialize;
+
+use crate::{CLI_DAY_RANGE, YEAR, web};
+
+/// The timestamp at which a star was obtained
+#[derive(Debug, Clone, Deserialize)]
+struct Star {
+    get_star_ts: i64,
+}
+
+/// A member of a private leaderboard
+#[derive(Debug, Clone, Deserialize)]
+struct Member {
+    id: u64,
+    name: Option<String>,
+    local_score: u64,
+    stars: u32,
+    last_star_ts: i64,
+    /// For each day, the stars obtained for each part (1 or 2)
+    completion_day_level: BTreeMap<u32, BTreeMap<u8, Star>>,
+}
+
+impl Member {
+    /// Get the name of the member, or a placeholder for anonymous users
+    fn display_name(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("(anonymous user #{})", self.id))
+    }
+
+    /// Get the completion timestamp of a part for a given day
+    fn star_ts(&self, day: u32, part: u8) -> Option<i64> {
+        self.completion_day_level
+            .get(&day)
+            .and_then(|parts| parts.get(&part))
+            .map(|s| s.get_star_ts)
+    }
+}
+
+/// A private leaderboard, as returned by the Advent of Code API
+#[derive(Debug, Clone, Deserialize)]
+pub struct Leaderboard {
+    members: HashMap<String, Member>,
+}
+
+impl Leaderboard {
+    /// Parse a leaderboard from its JSON representation
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).context("parsing leaderboard JSON")
+    }
+
+    /// Members sorted by descending local score, then by ascending time of their last star
+    fn ranked_members(&self) -> Vec<&Member> {
+        let mut members: Vec<_> = self.members.values().collect();
+        members.sort_by_key(|m| (Reverse(m.local_score), m.last_star_ts));
+        members
+    }
+
+    /// Render the overall ranking with the stars obtained each day
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let name_width = self.name_width();
+        write!(
+            out,
+            "{:>3}  {:<name_width$} {:>5} {:>5}  ",
+            "#", "Name", "Score", "Stars"
+        )
+        .unwrap();
+        for day in CLI_DAY_RANGE {
+            write!(out, "{day:<3}").unwrap();
+        }
+        out.push('\n');
+        for (rank, member) in self.ranked_members().into_iter().enumerate() {
+            write!(
+                out,
+                "{:>3}) {:<name_width$} {:>5} {:>5}  ",
+                rank + 1,
+                member.display_name(),
+                member.local_score,
+                member.stars
+            )
+            .unwrap();
+            for day in CLI_DAY_RANGE {
+                let day = day as u32;
+                let cell = match (member.star_ts(day, 1), member.star_ts(day, 2)) {
+                    (Some(_), Some(_)) => "**",
+                    (Some(_), None) => "* ",
+                    _ => ". ",
+                };
+                write!(out, "{cell} ").unwrap();
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Render the completion timestamps for a given day
+    ///
+    /// The part durations are measured from the puzzle unlock time, and the delta is the time between the two parts.
+    pub fn render_day(&self, day: u32) -> String {
+        let mut out = String::new();
+        let name_width = self.name_width();
+        let unlock = unlock_time(day);
+        writeln!(
+            out,
+            "{:>3}  {:<name_width$} {:>19} {:>10} {:>19} {:>10} {:>10}",
+            "#", "Name", "Part 1", "Time", "Part 2", "Time", "Delta"
+        )
+        .unwrap();
+        let mut members: Vec<_> = self
+            .members
+            .values()
+            .filter_map(|m| m.star_ts(day, 1).map(|p1| (m, p1, m.star_ts(day, 2))))
+            .collect();
+        // rank by part 2 completion first, then by part 1 completion
+        members.sort_by_key(|(_, p1, p2)| (p2.unwrap_or(i64::MAX), *p1));
+        for (rank, (member, p1, p2)) in members.into_iter().enumerate() {
+            write!(
+                out,
+                "{:>3}) {:<name_width$} {:>19} {:>10} ",
+                rank + 1,
+                member.display_name(),
+                format_ts(p1),
+                format_duration(p1 - unlock)
+            )
+            .unwrap();
+            match p2 {
+                Some(p2) => writeln!(
+                    out,
+                    "{:>19} {:>10} {:>10}",
+                    format_ts(p2),
+                    format_duration(p2 - unlock),
+                    format_duration(p2 - p1)
+                )
+                .unwrap(),
+                None => writeln!(out, "{:>19} {:>10} {:>10}", "-", "-", "-").unwrap(),
+            }
+        }
+        out
+    }
+
+    /// Width of the name column
+    fn name_width(&self) -> usize {
+        self.members
+            .values()
+            .map(|m| m.display_name().chars().count())
+            .max()
+            .unwrap_or_default()
+            .max(4)
+    }
+}
+
+/// The unix timestamp at which the puzzle for a day unlocks (midnight EST)
+fn unlock_time(day: u32) -> i64 {
+    Utc.with_ymd_and_hms(YEAR as i32, 12, day, 5, 0, 0)
+        .unwrap()
+        .timestamp()
+}
+
+/// Format a unix timestamp in the local timezone
+fn format_ts(ts: i64) -> String {
+    DateTime::from_timestamp(ts, 0).map_or_else(
+        || "?".to_string(),
+        |dt| {
+            dt.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        },
+    )
+}
+
+/// Format a number of seconds as `hh:mm:ss`
+fn format_duration(secs: i64) -> String {
+    if secs < 0 {
+        return "-".to_string();
+    }
+    format!("{}:{:02}:{:02}", secs / 3600, (secs / 60) % 60, secs % 60)
+}
+
+/// Get the JSON for a leaderboard, either from the cache if it's recent enough, or from the Advent of Code website
+fn fetch_json(id: u64) -> Result<String> {
+    let url = format!("https://adventofcode.com/{YEAR}/leaderboard/private/view/{id}.json");
+    web::get_cached(&url, &format!("leaderboard_{YEAR}_{id}.json"), |text| {
+        // make sure we got a leaderboard and not an error page before caching
+        Leaderboard::from_json(text).map(|_| ())
+    })
+}
+
+/// Display a private leaderboard, fetched from the website or read from a saved JSON file
+pub fn show_leaderboard(id: Option<u64>, file: Option<&Path>, day: Option<u32>) -> Result<()> {
+    let json = match (file, id) {
+        (Some(file), _) => fs::read_to_string(file).context("reading leaderboard file")?,
+        (None, Some(id)) => fetch_json(id)?,
+        (None, None) => bail!("either a leaderboard ID or a file is required"),
+    };
+    let leaderboard = Leaderboard::from_json(&json)?;
+    print!("{}", leaderboard.render());
+    if let Some(day) = day {
+        println!();
+        println!("Day {day}:");
+        print!("{}", leaderboard.render_day(day));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = r#"{
+  "owner_id": 1,
+  "event": "2025",
+  "members": {
+    "1": {
+      "id": 1,
+      "name": "beeb",
+      "stars": 3,
+      "local_score": 11,
+      "global_score": 0,
+      "last_star_ts": 1764569400,
+      "completion_day_level": {
+        "1": {
+          "1": { "get_star_ts": 1764565500, "star_index": 0 },
+          "2": { "get_star_ts": 1764569400, "star_index": 1 }
+        },
+        "2": {
+          "1": { "get_star_ts": 1764651900, "star_index": 2 }
+        }
+      }
+    },
+    "2": {
+      "id": 2,
+      "name": null,
+      "stars": 0,
+      "local_score": 0,
+      "global_score": 0,
+      "last_star_ts": 0,
+      "completion_day_level": {}
+    }
+  }
+}"#;
+
+    #[test]
+    fn test_render() {
+        let leaderboard = Leaderboard::from_json(INPUT).unwrap();
+        let rendered = leaderboard.render();
+        let lines: Vec<_> = rendered.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("  1) beeb"));
+        assert!(lines[1].contains(" 11     3  ** *  . "));
+        assert!(lines[2].starts_with("  2) (anonymous user #2)"));
+    }
+
+    #[test]
+    fn test_render_day() {
+        let leaderboard = Leaderboard::from_json(INPUT).unwrap();
+        let rendered = leaderboard.render_day(1);
+        let lines: Vec<_> = rendered.lines().collect();
+        assert_eq!(lines.len(), 2);
+        // part 1 after 5 minutes, part 2 after 1h10, delta is 1h05
+        assert!(lines[1].starts_with("  1) beeb"));
+        assert!(lines[1].contains("    0:05:00 "));
+        assert!(lines[1].ends_with("    1:10:00    1:05:00"));
+    }
+}
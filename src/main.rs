@@ -1,19 +1,30 @@
-use std::{fs, ops::RangeInclusive};
+use std::{fs, ops::RangeInclusive, path::PathBuf};
 
 use anyhow::{Context as _, Result, bail};
 use chrono::{Datelike, Local};
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use itertools::Itertools as _;
 
-#[allow(clippy::wildcard_imports)]
-use days::*;
+use alloc::CountingAllocator;
+use answers::Answers;
+use days::{Day, RunOptions, RunReport, day08, day09, day11, day12, with_day};
 
+mod alloc;
+mod answers;
 mod days;
+mod leaderboard;
+mod status;
+mod web;
 
 const YEAR: usize = 2025; // change this if needed, add the missing day modules if < 2025
 const CLI_DAY_RANGE: RangeInclusive<i64> = if YEAR < 2025 { 1..=25 } else { 1..=12 };
 const VALID_DAY_RANGE: RangeInclusive<u32> =
     (*CLI_DAY_RANGE.start() as u32)..=(*CLI_DAY_RANGE.end() as u32);
 
+/// Counts allocations when running with `--mem`
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
 /// Advent of Code
 #[derive(Parser)]
 #[command(author, version)]
@@ -34,6 +45,36 @@ enum Commands {
         day: Option<u32>,
         #[arg(short, long, help = "Runs all days sequentially")]
         all: bool,
+        #[arg(short, long, help = "Reports allocations and peak memory usage")]
+        mem: bool,
+        #[arg(
+            short,
+            long,
+            help = "Explains how the answers were obtained, if supported by the day"
+        )]
+        explain: bool,
+        #[arg(
+            long,
+            help = "Runs on the example input in inputs/dayNN.example.txt, without recording the answers"
+        )]
+        example: bool,
+    },
+    /// Benchmark the code for one or all days, solving both parts together and each part on its own
+    Bench {
+        #[arg(
+            value_parser = clap::value_parser!(u32).range(CLI_DAY_RANGE),
+            help = "The number of the day you want to benchmark")
+        ]
+        day: Option<u32>,
+        #[arg(short, long, help = "Benchmarks all days sequentially")]
+        all: bool,
+        #[arg(
+            short = 'n',
+            long,
+            default_value_t = 10,
+            help = "How many times to run each part"
+        )]
+        iterations: u32,
     },
     /// Get the input file for one or all days
     Get {
@@ -45,23 +86,175 @@ enum Commands {
         #[arg(short, long, help = "Downloads input for all days sequentially")]
         all: bool,
     },
+    /// Show the progress for each day: implementation, input, answers and stars
+    Status,
+    /// Show a private leaderboard
+    Leaderboard {
+        #[arg(
+            required_unless_present = "file",
+            help = "The ID of the private leaderboard (not needed with --file)"
+        )]
+        id: Option<u64>,
+        #[arg(
+            short,
+            long,
+            help = "Read the leaderboard from a saved JSON file instead of fetching it"
+        )]
+        file: Option<PathBuf>,
+        #[arg(
+            short,
+            long,
+            value_parser = clap::value_parser!(u32).range(CLI_DAY_RANGE),
+            help = "Show the completion times for a given day")
+        ]
+        day: Option<u32>,
+    },
+    /// Export the single-linkage clustering dendrogram of day 8's junction boxes
+    Dendrogram {
+        #[arg(short, long, help = "The input file (defaults to the input for day 8)")]
+        input: Option<PathBuf>,
+        #[arg(
+            short,
+            long,
+            value_enum,
+            default_value_t = DendrogramFormat::Json,
+            conflicts_with_all = ["threshold", "edges"],
+            help = "The output format of the dendrogram (not used with --threshold or --edges)"
+        )]
+        format: DendrogramFormat,
+        #[arg(
+            short,
+            long,
+            conflicts_with = "edges",
+            help = "Show the cluster sizes when connecting pairs up to this squared distance"
+        )]
+        threshold: Option<usize>,
+        #[arg(
+            short,
+            long,
+            help = "Show the cluster sizes after connecting this many closest pairs"
+        )]
+        edges: Option<usize>,
+    },
+    /// Draw day 9's polygon with the largest rectangles of both parts, as an SVG image
+    Polygon {
+        #[arg(short, long, help = "The input file (defaults to the input for day 9)")]
+        input: Option<PathBuf>,
+    },
+    /// Count the paths between two of day 11's devices which visit a set of waypoints
+    Paths {
+        #[arg(
+            short,
+            long,
+            help = "The input file (defaults to the input for day 11)"
+        )]
+        input: Option<PathBuf>,
+        #[arg(long, default_value = "svr", help = "The device the paths start from")]
+        from: String,
+        #[arg(long, default_value = "out", help = "The device the paths end at")]
+        to: String,
+        #[arg(
+            long,
+            value_delimiter = ',',
+            help = "Comma-separated devices which the paths must visit"
+        )]
+        via: Vec<String>,
+        #[arg(long, help = "Visit the waypoints in the given order")]
+        ordered: bool,
+        #[arg(
+            long,
+            help = "Print the graph in Graphviz DOT format, with the number of paths through each device"
+        )]
+        dot: bool,
+    },
+    /// Show how day 12's presents are packed into each region
+    Packing {
+        #[arg(
+            short,
+            long,
+            help = "The input file (defaults to the input for day 12)"
+        )]
+        input: Option<PathBuf>,
+        #[arg(short, long, help = "Only show this region (starting from 1)")]
+        region: Option<usize>,
+        #[arg(
+            long,
+            value_delimiter = ',',
+            conflicts_with = "region",
+            help = "Find the smallest region which fits these comma-separated numbers of each present"
+        )]
+        smallest: Vec<usize>,
+        #[arg(
+            long,
+            requires = "smallest",
+            help = "Only look for square regions with --smallest"
+        )]
+        square: bool,
+        #[arg(
+            short,
+            long,
+            value_enum,
+            default_value_t = PackingFormat::Ansi,
+            help = "The output format"
+        )]
+        format: PackingFormat,
+    },
+}
+
+/// Output format for the packing layouts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum PackingFormat {
+    /// Letters for the pieces
+    Ascii,
+    /// Letters and colours for the pieces, for the terminal
+    Ansi,
+    /// SVG image, for a single region
+    Svg,
+}
+
+/// Output format for the dendrogram
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum DendrogramFormat {
+    Json,
+    Newick,
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Run { day, all } => {
+        Commands::Run {
+            day,
+            all,
+            mem,
+            explain,
+            example,
+        } => {
+            let options = RunOptions { mem, explain };
             if all {
-                return run_all_days();
+                return run_all_days(options, example);
             }
             if let Some(day) = day {
-                return run_day(day);
+                return run_day(day, options, example);
             }
             println!("No day parameter specified, attempting to run today's code");
             let now_day = get_today()?;
             println!("Running day {now_day}");
-            run_day(now_day)
+            run_day(now_day, options, example)
+        }
+        Commands::Bench {
+            day,
+            all,
+            iterations,
+        } => {
+            if all {
+                return bench_all_days(iterations);
+            }
+            if let Some(day) = day {
+                return bench_day(day, iterations);
+            }
+            println!("No day parameter specified, attempting to benchmark today's code");
+            bench_day(get_today()?, iterations)
         }
         Commands::Get { day, all } => {
             if all {
@@ -75,9 +268,171 @@ fn main() -> Result<()> {
             println!("Getting input for day {now_day}");
             download_input(now_day)
         }
+        Commands::Status => status::show_status(),
+        Commands::Leaderboard { id, file, day } => {
+            leaderboard::show_leaderboard(id, file.as_deref(), day)
+        }
+        Commands::Dendrogram {
+            input,
+            format,
+            threshold,
+            edges,
+        } => dendrogram(input, format, threshold, edges),
+        Commands::Polygon { input } => polygon(input),
+        Commands::Paths {
+            input,
+            from,
+            to,
+            via,
+            ordered,
+            dot,
+        } => paths(input, &from, &to, &via, ordered, dot),
+        Commands::Packing {
+            input,
+            region: _,
+            smallest,
+            square,
+            format,
+        } if !smallest.is_empty() => smallest_region(input, &smallest, square, format),
+        Commands::Packing {
+            input,
+            region,
+            format,
+            ..
+        } => packing(input, region, format),
     }
 }
 
+/// Read the input for a day, from the given path or from the default location
+fn read_input(day: u32, path: Option<PathBuf>) -> Result<String> {
+    let path = path.unwrap_or_else(|| format!("inputs/day{day:02}.txt").into());
+    fs::read_to_string(path).context("reading the input file")
+}
+
+/// Print day 8's dendrogram, or the cluster sizes at a given threshold
+fn dendrogram(
+    input: Option<PathBuf>,
+    format: DendrogramFormat,
+    threshold: Option<usize>,
+    edges: Option<usize>,
+) -> Result<()> {
+    let input = read_input(8, input)?;
+    let points = day08::Day08::parse(&input)?;
+    let dendrogram = day08::Dendrogram::new(&points);
+    let sizes = match (threshold, edges) {
+        (Some(threshold), _) => Some(dendrogram.sizes_at_distance(threshold)),
+        (None, Some(edges)) => Some(dendrogram.sizes_at_edges(edges)),
+        (None, None) => None,
+    };
+    if let Some(sizes) = sizes {
+        println!("{} clusters", sizes.len());
+        println!("{}", sizes.iter().join(" "));
+        return Ok(());
+    }
+    match format {
+        DendrogramFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&dendrogram).context("serializing dendrogram")?
+        ),
+        DendrogramFormat::Newick => println!("{}", dendrogram.to_newick()),
+    }
+    Ok(())
+}
+
+/// Print day 9's polygon and its largest rectangles as an SVG image
+fn polygon(input: Option<PathBuf>) -> Result<()> {
+    let input = read_input(9, input)?;
+    let points = day09::Day09::parse(&input)?;
+    print!("{}", day09::to_svg(&points));
+    Ok(())
+}
+
+/// Print the number of paths between two of day 11's devices going through the waypoints, or the annotated graph
+fn paths(
+    input: Option<PathBuf>,
+    from: &str,
+    to: &str,
+    via: &[String],
+    ordered: bool,
+    dot: bool,
+) -> Result<()> {
+    let input = read_input(11, input)?;
+    let server = day11::Day11::parse(&input)?;
+    let via: Vec<_> = via.iter().map(String::as_str).collect();
+    if dot {
+        print!("{}", server.to_dot(from, to, &via)?);
+        return Ok(());
+    }
+    let count = server.count_paths(from, to, &via, ordered)?;
+    println!("{count}");
+    Ok(())
+}
+
+/// Print the layout of day 12's presents in one or all regions
+fn packing(input: Option<PathBuf>, region: Option<usize>, format: PackingFormat) -> Result<()> {
+    let input = read_input(12, input)?;
+    let puzzle = day12::Day12::parse(&input)?;
+    let regions: Vec<_> = match region {
+        Some(i) => {
+            let Some(region) = i.checked_sub(1).and_then(|i| puzzle.regions().get(i)) else {
+                bail!("there is no region {i}");
+            };
+            vec![(i, region)]
+        }
+        None if format == PackingFormat::Svg => bail!("choose a region with --region for SVG"),
+        None => (1..).zip(puzzle.regions()).collect(),
+    };
+    for (i, region) in regions {
+        let packing = puzzle.pack(region);
+        if format == PackingFormat::Svg {
+            let Some(packing) = packing else {
+                bail!("the presents don't fit in region {i}");
+            };
+            print!("{}", packing.to_svg(puzzle.shapes()));
+            continue;
+        }
+        println!("Region {i} ({region})");
+        match packing {
+            Some(packing) => println!(
+                "{}",
+                packing.render(puzzle.shapes(), format == PackingFormat::Ansi)
+            ),
+            None => println!("The presents don't fit\n"),
+        }
+    }
+    Ok(())
+}
+
+/// Print the smallest region which fits the given numbers of day 12's presents, and how they are packed
+fn smallest_region(
+    input: Option<PathBuf>,
+    counts: &[usize],
+    square: bool,
+    format: PackingFormat,
+) -> Result<()> {
+    let input = read_input(12, input)?;
+    let puzzle = day12::Day12::parse(&input)?;
+    if counts.len() != puzzle.shapes().len() {
+        bail!(
+            "expected a number for each of the {} presents",
+            puzzle.shapes().len()
+        );
+    }
+    let Some((region, packing)) = puzzle.smallest_region(counts, square) else {
+        bail!("there is no region which fits these presents");
+    };
+    if format == PackingFormat::Svg {
+        print!("{}", packing.to_svg(puzzle.shapes()));
+    } else {
+        println!("Smallest region: {region}");
+        println!(
+            "{}",
+            packing.render(puzzle.shapes(), format == PackingFormat::Ansi)
+        );
+    }
+    Ok(())
+}
+
 fn get_today() -> Result<u32> {
     let now = Local::now();
     let now_day = now.day();
@@ -89,28 +444,53 @@ fn get_today() -> Result<u32> {
 }
 
 #[expect(const_item_mutation)]
-fn run_all_days() -> Result<()> {
-    VALID_DAY_RANGE.try_for_each(run_day)
+fn run_all_days(options: RunOptions, example: bool) -> Result<()> {
+    VALID_DAY_RANGE.try_for_each(|day| run_day(day, options, example))
 }
 
-fn run_day(day: u32) -> Result<()> {
+/// Run a day with the given input file
+fn run<D: Day>(input_file: &str, options: RunOptions) -> Result<RunReport> {
+    D::run_day(input_file, options)
+}
+
+/// Run a day on its input, or on its example input, in which case the answers aren't recorded
+fn run_day(day: u32, options: RunOptions, example: bool) -> Result<()> {
     println!("======== DAY {day} ========");
-    let input_file = &format!("inputs/day{day:02}.txt");
-    match day {
-        1 => day01::Day01::run_day(input_file),
-        2 => day02::Day02::run_day(input_file),
-        3 => day03::Day03::run_day(input_file),
-        4 => day04::Day04::run_day(input_file),
-        5 => day05::Day05::run_day(input_file),
-        6 => day06::Day06::run_day(input_file),
-        7 => day07::Day07::run_day(input_file),
-        8 => day08::Day08::run_day(input_file),
-        9 => day09::Day09::run_day(input_file),
-        10 => day10::Day10::run_day(input_file),
-        11 => day11::Day11::run_day(input_file),
-        12 => day12::Day12::run_day(input_file),
-        d => bail!("provided unsupported day {d}"),
+    let input_file = &if example {
+        format!("inputs/day{day:02}.example.txt")
+    } else {
+        format!("inputs/day{day:02}.txt")
+    };
+    let Some(report) = with_day!(day, run(input_file, options)) else {
+        bail!("provided unsupported day {day}");
+    };
+    if example {
+        return report.map(|_| ());
+    }
+    let mut answers = Answers::load()?;
+    for warning in answers.record(day, &report?) {
+        println!("Warning: {warning}");
     }
+    answers.save()
+}
+
+#[expect(const_item_mutation)]
+fn bench_all_days(iterations: u32) -> Result<()> {
+    VALID_DAY_RANGE.try_for_each(|day| bench_day(day, iterations))
+}
+
+/// Benchmark a day with the given input file
+fn bench<D: Day>(input_file: &str, iterations: u32) -> Result<()> {
+    D::bench(input_file, iterations)
+}
+
+fn bench_day(day: u32, iterations: u32) -> Result<()> {
+    println!("======== DAY {day} ========");
+    let input_file = &format!("inputs/day{day:02}.txt");
+    let Some(res) = with_day!(day, bench(input_file, iterations)) else {
+        bail!("provided unsupported day {day}");
+    };
+    res
 }
 
 #[expect(const_item_mutation)]
@@ -119,21 +499,10 @@ fn download_all_inputs() -> Result<()> {
 }
 
 fn download_input(day: u32) -> Result<()> {
-    // Read session cookie from .session file
-    let session = fs::read_to_string(".session").context("reading .session file")?;
-    let session = session.trim();
     let url = format!("https://adventofcode.com/{YEAR}/day/{day}/input");
-
-    let mut response = ureq::get(url)
-        .header(ureq::http::header::COOKIE, format!("session={session};"))
-        .call()
-        .with_context(|| format!("retrieving the input for day {day}. Do you have the correct session cookie in the .session file?"))?;
-    let text = response
-        .body_mut()
-        .read_to_string()
-        .context("decoding response body as text")?;
+    let text = web::get(&url).with_context(|| format!("retrieving the input for day {day}"))?;
     let path = format!("inputs/day{day:02}.txt");
     fs::write(&path, text.trim_end_matches('\n')).context("writing input to file")?;
-    println!("Successfully downloaded input to {}", &path);
+    println!("Successfully downloaded input to {path}");
     Ok(())
 }
@@ -0,0 +1,222 @@
+use std::{collections::BTreeMap, path::Path};
+
+use anyhow::{Result, bail};
+
+use crate::{
+    VALID_DAY_RANGE, YEAR,
+    answers::{Answers, PartAnswer},
+    days::{Day, with_day},
+    web,
+};
+
+/// Parse the number of stars earned for each day from the event page (calendar)
+fn parse_stars(html: &str) -> BTreeMap<u32, u8> {
+    let mut stars = BTreeMap::new();
+    for day in VALID_DAY_RANGE {
+        // the trailing comma avoids matching day 10 when looking for day 1
+        let label = format!("aria-label=\"Day {day}, ");
+        let Some(pos) = html.find(&label) else {
+            continue;
+        };
+        let rest = &html[pos + label.len()..];
+        if rest.starts_with("two stars") {
+            stars.insert(day, 2);
+        } else if rest.starts_with("one star") {
+            stars.insert(day, 1);
+        }
+    }
+    stars
+}
+
+/// Retrieve the stars earned for each day from the event page, falling back to an outdated cached copy if needed
+fn fetch_stars() -> Result<BTreeMap<u32, u8>> {
+    let url = format!("https://adventofcode.com/{YEAR}");
+    let name = format!("event_{YEAR}.html");
+    let html = match web::get_cached(&url, &name, |text| {
+        if !text.contains("calendar") {
+            bail!("the event page doesn't contain a calendar");
+        }
+        Ok(())
+    }) {
+        Ok(html) => html,
+        Err(e) => match web::read_cache(&name) {
+            Some(html) => {
+                println!("Could not refresh the event page ({e}), using cached copy");
+                html
+            }
+            None => return Err(e),
+        },
+    };
+    Ok(parse_stars(&html))
+}
+
+/// Parse the answers accepted for each part from a puzzle page, in order
+fn parse_submitted(html: &str) -> Vec<String> {
+    const PREFIX: &str = "Your puzzle answer was <code>";
+    html.match_indices(PREFIX)
+        .filter_map(|(pos, _)| {
+            let rest = &html[pos + PREFIX.len()..];
+            rest.find("</code>").map(|end| rest[..end].to_string())
+        })
+        .collect()
+}
+
+/// Retrieve the accepted answers of a day from its puzzle page, falling back to an outdated cached copy if needed
+fn fetch_submitted(day: u32) -> Result<Vec<String>> {
+    let url = format!("https://adventofcode.com/{YEAR}/day/{day}");
+    let name = format!("day_{YEAR}_{day:02}.html");
+    let html = match web::get_cached(&url, &name, |text| {
+        if !text.contains("<article") {
+            bail!("the puzzle page doesn't contain the puzzle");
+        }
+        Ok(())
+    }) {
+        Ok(html) => html,
+        Err(e) => match web::read_cache(&name) {
+            Some(html) => {
+                println!(
+                    "Could not refresh the puzzle page for day {day} ({e}), using cached copy"
+                );
+                html
+            }
+            None => return Err(e),
+        },
+    };
+    Ok(parse_submitted(&html))
+}
+
+/// Whether part 2 of a day is a placeholder
+fn part_2_placeholder<D: Day>() -> bool {
+    D::PART_2_PLACEHOLDER
+}
+
+/// Describe the state of the answer for a part
+///
+/// An answer is "verified" if it's the one accepted on the website, and "wrong" if it isn't. It's only "starred" if the
+/// star for the part was earned but the accepted answer couldn't be retrieved.
+fn answer_status(answer: Option<&PartAnswer>, submitted: Option<&str>, star: bool) -> &'static str {
+    match (answer, submitted) {
+        (Some(answer), Some(submitted)) if answer.answer == submitted => "verified",
+        (Some(_), Some(_)) => "wrong",
+        (Some(_), None) if star => "starred",
+        (Some(_), None) => "recorded",
+        (None, _) => "-",
+    }
+}
+
+/// Print a per-day overview of the progress for the configured year
+pub fn show_status() -> Result<()> {
+    let mut answers = Answers::load()?;
+    match fetch_stars() {
+        Ok(stars) => {
+            for (day, n) in stars {
+                answers.set_stars(day, n);
+            }
+        }
+        Err(e) => {
+            println!("Could not retrieve the event page ({e:#}), using the local answers store");
+        }
+    }
+    for day in VALID_DAY_RANGE {
+        let Some(day_answers) = answers.get(day) else {
+            continue;
+        };
+        // accepted answers never change, so the puzzle page is only fetched when a star was earned since last time
+        let expected = if with_day!(day, part_2_placeholder()) == Some(true) {
+            day_answers.stars.min(1)
+        } else {
+            day_answers.stars
+        };
+        if day_answers.submitted.len() >= usize::from(expected) {
+            continue;
+        }
+        match fetch_submitted(day) {
+            Ok(submitted) => answers.set_submitted(day, submitted),
+            Err(e) => println!("Could not retrieve the puzzle page for day {day} ({e:#})"),
+        }
+    }
+    answers.save()?;
+
+    println!("Day  Code  Input  Part 1    Part 2       Last time  Stars");
+    for day in VALID_DAY_RANGE {
+        let placeholder = with_day!(day, part_2_placeholder());
+        let code = if placeholder.is_some() { "yes" } else { "no" };
+        let input = if Path::new(&format!("inputs/day{day:02}.txt")).exists() {
+            "yes"
+        } else {
+            "no"
+        };
+        let day_answers = answers.get(day).cloned().unwrap_or_default();
+        let part_1 = answer_status(
+            day_answers.part_1.as_ref(),
+            day_answers.submitted(1),
+            day_answers.stars >= 1,
+        );
+        let part_2 = if placeholder == Some(true) {
+            "placeholder"
+        } else {
+            answer_status(
+                day_answers.part_2.as_ref(),
+                day_answers.submitted(2),
+                day_answers.stars >= 2,
+            )
+        };
+        let time = day_answers
+            .duration_both
+            .or_else(|| {
+                day_answers
+                    .part_1
+                    .iter()
+                    .chain(day_answers.part_2.iter())
+                    .filter_map(|a| a.duration)
+                    .reduce(|a, b| a + b)
+            })
+            .map_or_else(|| "-".to_string(), |d| format!("{d:.2?}"));
+        let stars = match day_answers.stars {
+            0 => "-",
+            1 => "*",
+            _ => "**",
+        };
+        println!("{day:>3}  {code:<4}  {input:<5}  {part_1:<8}  {part_2:<11}  {time:>9}  {stars}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = r#"<pre class="calendar">
+<a aria-label="Day 1, two stars" href="/2025/day/1" class="calendar-day1 calendar-verycomplete">
+<a aria-label="Day 2, one star" href="/2025/day/2" class="calendar-day2 calendar-complete">
+<a aria-label="Day 10" href="/2025/day/10" class="calendar-day10">
+</pre>"#;
+
+    #[test]
+    fn test_parse_stars() {
+        let stars = parse_stars(INPUT);
+        assert_eq!(stars, BTreeMap::from([(1, 2), (2, 1)]));
+    }
+
+    #[test]
+    fn test_parse_submitted() {
+        let html = r#"<article class="day-desc"><h2>--- Day 1: Secret Entrance ---</h2></article>
+<p>Your puzzle answer was <code>1234</code>.</p><article class="day-desc"><h2 id="part2">--- Part Two ---</h2></article>
+<p>Your puzzle answer was <code>5678</code>.</p><p class="day-success">Both parts of this puzzle are complete!</p>"#;
+        assert_eq!(parse_submitted(html), ["1234", "5678"]);
+        assert_eq!(parse_submitted("<article></article>"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_answer_status() {
+        let answer = PartAnswer {
+            answer: "42".to_string(),
+            duration: None,
+        };
+        assert_eq!(answer_status(Some(&answer), Some("42"), true), "verified");
+        assert_eq!(answer_status(Some(&answer), Some("41"), true), "wrong");
+        assert_eq!(answer_status(Some(&answer), None, true), "starred");
+        assert_eq!(answer_status(Some(&answer), None, false), "recorded");
+        assert_eq!(answer_status(None, Some("42"), true), "-");
+    }
+}
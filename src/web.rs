@@ -0,0 +1,73 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Context as _, Result};
+
+/// The minimum delay between two requests for the same resource, as requested by the Advent of Code maintainers
+const REFRESH_INTERVAL: Duration = Duration::from_mins(15);
+
+/// Directory where fetched resources are cached
+const CACHE_DIR: &str = ".cache";
+
+/// Read the session cookie from the .session file
+pub fn read_session() -> Result<String> {
+    let session = fs::read_to_string(".session").context("reading .session file")?;
+    Ok(session.trim().to_string())
+}
+
+/// Retrieve a page from the Advent of Code website, authenticated with the session cookie
+pub fn get(url: &str) -> Result<String> {
+    let session = read_session()?;
+    let mut response = ureq::get(url)
+        .header(ureq::http::header::COOKIE, format!("session={session};"))
+        .call()
+        .with_context(|| {
+            format!(
+                "retrieving {url}. Do you have the correct session cookie in the .session file?"
+            )
+        })?;
+    response
+        .body_mut()
+        .read_to_string()
+        .context("decoding response body as text")
+}
+
+/// Path to the cached copy of a resource
+fn cache_path(name: &str) -> PathBuf {
+    Path::new(CACHE_DIR).join(name)
+}
+
+/// Read a cached resource, regardless of its age
+pub fn read_cache(name: &str) -> Option<String> {
+    fs::read_to_string(cache_path(name)).ok()
+}
+
+/// Retrieve a page from the cache if it's recent enough, or from the Advent of Code website
+///
+/// The `validate` function is called on freshly downloaded content, to avoid caching error pages.
+pub fn get_cached(url: &str, name: &str, validate: impl Fn(&str) -> Result<()>) -> Result<String> {
+    let path = cache_path(name);
+    if let Ok(age) = fs::metadata(&path)
+        .and_then(|m| m.modified())
+        .map(|modified| {
+            SystemTime::now()
+                .duration_since(modified)
+                .unwrap_or_default()
+        })
+        && age < REFRESH_INTERVAL
+    {
+        println!(
+            "Using cached {name} (next refresh allowed in {}s)",
+            REFRESH_INTERVAL.saturating_sub(age).as_secs()
+        );
+        return fs::read_to_string(&path).context("reading cached file");
+    }
+    let text = get(url)?;
+    validate(&text)?;
+    fs::create_dir_all(CACHE_DIR).context("creating cache directory")?;
+    fs::write(&path, &text).context("writing cache file")?;
+    Ok(text)
+}